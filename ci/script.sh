@@ -13,6 +13,7 @@ main() {
 
     cross test --target $TARGET
     cross test --target $TARGET --release
+    cross test --target $TARGET --features uca
 }
 
 # we don't run the "test phase" when doing deploys
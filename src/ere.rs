@@ -0,0 +1,479 @@
+//! POSIX extended regular expressions, as used by `yesexpr` and `noexpr` of `LC_MESSAGES`.
+//!
+//! Supports the full ERE syntax except back-references, which EREs don't have anyway, and
+//! multi-character collating elements. Only tells whether the expression matches anywhere in the
+//! text, which allows matching in linear time with a Pike VM.
+
+/// Upper bound of `{m,n}` repetition counts, `RE_DUP_MAX` in C.
+const DUP_MAX: u32 = 255;
+
+/// Limit on the size of the compiled program, mostly against nested counted repetitions.
+const MAX_PROGRAM: usize = 10000;
+
+/// Maximum nesting of parentheses and repetitions, so the recursion stays shallow.
+const MAX_NESTING: usize = 32;
+
+/// Compiled regular expression.
+#[derive(Debug, Clone)]
+pub struct Regex {
+    program: Vec<Inst>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Set(Set),
+    Start,
+    End,
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat(Box<Node>, u32, Option<u32>),
+}
+
+/// Bracket expression.
+#[derive(Debug, Clone)]
+struct Set {
+    negated: bool,
+    ranges: Vec<(char, char)>,
+    classes: Vec<fn(char) -> bool>,
+}
+
+impl Set {
+    fn matches(&self, c: char) -> bool {
+        let found = self.ranges.iter().any(|&(from, to)| from <= c && c <= to)
+            || self.classes.iter().any(|class| class(c));
+        found != self.negated
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Inst {
+    Char(char),
+    Any,
+    Set(Set),
+    Start,
+    End,
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+impl Regex {
+    /// Compiles the expression, returning `None` if it is not valid.
+    pub fn new(pattern: &str) -> Option<Regex> {
+        let mut parser = Parser { chars: pattern.chars().collect(), pos: 0, nesting: 0 };
+        let node = parser.alternation()?;
+        if parser.pos < parser.chars.len() {
+            // unbalanced `)`
+            return None;
+        }
+        let mut program = Vec::new();
+        compile(&node, &mut program)?;
+        program.push(Inst::Match);
+        Some(Regex { program })
+    }
+
+    /// Returns whether the expression matches any part of `text`.
+    pub fn is_match(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        for pos in 0..=text.len() {
+            // a new attempt starts at every position
+            if self.add_thread(&mut current, 0, pos, &text) {
+                return true;
+            }
+            if pos == text.len() {
+                break;
+            }
+            let c = text[pos];
+            for &pc in &current.list {
+                let step = match self.program[pc] {
+                    Inst::Char(expected) => c == expected,
+                    Inst::Any => true,
+                    Inst::Set(ref set) => set.matches(c),
+                    _ => false,
+                };
+                if step && self.add_thread(&mut next, pc + 1, pos + 1, &text) {
+                    return true;
+                }
+            }
+            ::std::mem::swap(&mut current, &mut next);
+            next.clear();
+        }
+        false
+    }
+
+    /// Adds thread at `pc` and all threads reachable from it without consuming input. Returns
+    /// whether any of them is a match.
+    fn add_thread(&self, threads: &mut Threads, pc: usize, pos: usize, text: &[char]) -> bool {
+        // an explicit stack, as chains of jumps can be as long as the program
+        threads.stack.push(pc);
+        while let Some(pc) = threads.stack.pop() {
+            if threads.seen[pc] {
+                continue;
+            }
+            threads.seen[pc] = true;
+            match self.program[pc] {
+                Inst::Jump(target) => threads.stack.push(target),
+                Inst::Split(first, second) => {
+                    threads.stack.push(second);
+                    threads.stack.push(first);
+                }
+                Inst::Start if pos == 0 => threads.stack.push(pc + 1),
+                Inst::End if pos == text.len() => threads.stack.push(pc + 1),
+                Inst::Start | Inst::End => {}
+                Inst::Match => {
+                    threads.stack.clear();
+                    return true;
+                }
+                _ => threads.list.push(pc),
+            }
+        }
+        false
+    }
+}
+
+struct Threads {
+    seen: Vec<bool>,
+    list: Vec<usize>,
+    stack: Vec<usize>,
+}
+
+impl Threads {
+    fn new(len: usize) -> Threads {
+        Threads { seen: vec![false; len], list: Vec::new(), stack: Vec::new() }
+    }
+
+    fn clear(&mut self) {
+        for seen in &mut self.seen {
+            *seen = false;
+        }
+        self.list.clear();
+    }
+}
+
+fn compile(node: &Node, program: &mut Vec<Inst>) -> Option<()> {
+    if program.len() > MAX_PROGRAM {
+        return None;
+    }
+    match *node {
+        Node::Empty => {}
+        Node::Char(c) => program.push(Inst::Char(c)),
+        Node::Any => program.push(Inst::Any),
+        Node::Set(ref set) => program.push(Inst::Set(set.clone())),
+        Node::Start => program.push(Inst::Start),
+        Node::End => program.push(Inst::End),
+        Node::Concat(ref nodes) => {
+            for node in nodes {
+                compile(node, program)?;
+            }
+        }
+        Node::Alternation(ref nodes) => {
+            let mut jumps = Vec::new();
+            for (index, node) in nodes.iter().enumerate() {
+                if index + 1 < nodes.len() {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(node, program)?;
+                    jumps.push(program.len());
+                    program.push(Inst::Jump(0));
+                    let next = program.len();
+                    program[split] = Inst::Split(split + 1, next);
+                } else {
+                    compile(node, program)?;
+                }
+            }
+            let end = program.len();
+            for jump in jumps {
+                program[jump] = Inst::Jump(end);
+            }
+        }
+        Node::Repeat(ref node, min, max) => {
+            for _ in 0..min {
+                compile(node, program)?;
+            }
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(node, program)?;
+                    program.push(Inst::Jump(split));
+                    let end = program.len();
+                    program[split] = Inst::Split(split + 1, end);
+                }
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in min..max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(0, 0));
+                        compile(node, program)?;
+                    }
+                    let end = program.len();
+                    for split in splits {
+                        program[split] = Inst::Split(split + 1, end);
+                    }
+                }
+            }
+        }
+    }
+    if program.len() > MAX_PROGRAM { None } else { Some(()) }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    nesting: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).cloned()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn enter(&mut self) -> Option<()> {
+        self.nesting += 1;
+        if self.nesting > MAX_NESTING { None } else { Some(()) }
+    }
+
+    fn alternation(&mut self) -> Option<Node> {
+        let mut branches = vec![self.concat()?];
+        while self.eat('|') {
+            branches.push(self.concat()?);
+        }
+        Some(if branches.len() == 1 {
+            branches.pop().unwrap()
+        } else {
+            Node::Alternation(branches)
+        })
+    }
+
+    fn concat(&mut self) -> Option<Node> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.repeat(atom)?);
+        }
+        Some(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.pop().unwrap(),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn atom(&mut self) -> Option<Node> {
+        Some(match self.next()? {
+            '(' => {
+                self.enter()?;
+                let node = self.alternation()?;
+                if !self.eat(')') {
+                    return None;
+                }
+                self.nesting -= 1;
+                node
+            }
+            '[' => Node::Set(self.bracket()?),
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '\\' => Node::Char(self.next()?),
+            // a repetition with nothing to repeat
+            '*' | '+' | '?' | '{' => return None,
+            c => Node::Char(c),
+        })
+    }
+
+    fn repeat(&mut self, mut node: Node) -> Option<Node> {
+        let nesting = self.nesting;
+        loop {
+            let (min, max) = match self.peek() {
+                Some('*') => (0, None),
+                Some('+') => (1, None),
+                Some('?') => (0, Some(1)),
+                Some('{') => {
+                    self.pos += 1;
+                    let min = self.count()?;
+                    let max = if self.eat(',') {
+                        if self.peek() == Some('}') { None } else { Some(self.count()?) }
+                    } else {
+                        Some(min)
+                    };
+                    if max.filter(|&max| max < min).is_some() || self.peek() != Some('}') {
+                        return None;
+                    }
+                    (min, max)
+                }
+                _ => {
+                    self.nesting = nesting;
+                    return Some(node);
+                }
+            };
+            self.pos += 1;
+            self.enter()?;
+            node = Node::Repeat(Box::new(node), min, max);
+        }
+    }
+
+    fn count(&mut self) -> Option<u32> {
+        let start = self.pos;
+        let mut value = 0;
+        while let Some(digit) = self.peek().and_then(|c| c.to_digit(10)) {
+            value = value * 10 + digit;
+            if value > DUP_MAX {
+                return None;
+            }
+            self.pos += 1;
+        }
+        if self.pos > start { Some(value) } else { None }
+    }
+
+    /// Parses bracket expression after the opening `[`.
+    fn bracket(&mut self) -> Option<Set> {
+        let mut set = Set { negated: self.eat('^'), ranges: Vec::new(), classes: Vec::new() };
+        let mut first = true;
+        loop {
+            let c = self.next()?;
+            if c == ']' && !first {
+                return Some(set);
+            }
+            first = false;
+            let from = if c == '[' && self.eat(':') {
+                set.classes.push(self.class()?);
+                continue;
+            } else if c == '[' && (self.peek() == Some('=') || self.peek() == Some('.')) {
+                // equivalence classes and collating symbols only for single characters
+                let delimiter = self.next()?;
+                let c = self.next()?;
+                if !(self.eat(delimiter) && self.eat(']')) {
+                    return None;
+                }
+                c
+            } else {
+                c
+            };
+            let to = if self.peek() == Some('-') && self.chars.get(self.pos + 1) != Some(&']') {
+                self.pos += 1;
+                match self.next()? {
+                    '[' if self.eat('.') => {
+                        let c = self.next()?;
+                        if !(self.eat('.') && self.eat(']')) {
+                            return None;
+                        }
+                        c
+                    }
+                    c => c,
+                }
+            } else {
+                from
+            };
+            if to < from {
+                return None;
+            }
+            set.ranges.push((from, to));
+        }
+    }
+
+    /// Parses character class name after `[:`.
+    fn class(&mut self) -> Option<fn(char) -> bool> {
+        let start = self.pos;
+        while self.peek().filter(|&c| c != ':').is_some() {
+            self.pos += 1;
+        }
+        let name: String = self.chars[start..self.pos].iter().collect();
+        if !(self.eat(':') && self.eat(']')) {
+            return None;
+        }
+        Some(match &name[..] {
+            "alpha" => char::is_alphabetic,
+            "digit" => |c: char| c.is_ascii_digit(),
+            "alnum" => char::is_alphanumeric,
+            "upper" => char::is_uppercase,
+            "lower" => char::is_lowercase,
+            "space" => char::is_whitespace,
+            "blank" => |c: char| c == ' ' || c == '\t',
+            "punct" => |c: char| c.is_ascii_punctuation(),
+            "print" => |c: char| !c.is_control(),
+            "graph" => |c: char| !c.is_control() && !c.is_whitespace(),
+            "cntrl" => char::is_control,
+            "xdigit" => |c: char| c.is_ascii_hexdigit(),
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        Regex::new(pattern).expect(pattern).is_match(text)
+    }
+
+    #[test]
+    fn locale_expressions() {
+        assert!(matches("^[+1aAyY]", "ano"));
+        assert!(matches("^[-0nN]", "-"));
+        assert!(!matches("^[+1aAyY]", "ne"));
+        assert!(!matches("^[yY]", " y"));
+        assert!(matches("^([+1yYｙＹ]|はい|ハイ)", "はい"));
+        assert!(!matches("^([+1yYｙＹ]|はい|ハイ)", "いいえ"));
+        assert!(matches("^[+1yYДд]", "да"));
+    }
+
+    #[test]
+    fn syntax() {
+        assert!(matches("a.c", "xabcx"));
+        assert!(matches("^ab*c$", "ac"));
+        assert!(matches("^ab+c$", "abbbc"));
+        assert!(!matches("^ab+c$", "ac"));
+        assert!(matches("^ab?c$", "abc"));
+        assert!(matches("^a{2,3}$", "aaa"));
+        assert!(!matches("^a{2,3}$", "aaaa"));
+        assert!(matches("^a{2,}$", "aaaaa"));
+        assert!(matches("^(ab|cd){2}$", "abcd"));
+        assert!(matches("^(a*)*$", "aaa"));
+        assert!(matches("^(|x)y", "y"));
+        assert!(matches("a\\.b", "a.b"));
+        assert!(!matches("a\\.b", "axb"));
+        assert!(matches("^[]a-]+$", "]-a"));
+        assert!(matches("^[^a-c]$", "d"));
+        assert!(!matches("^[^a-c]$", "b"));
+        assert!(matches("^[[:digit:][:upper:]]+$", "A1Ž"));
+        assert!(matches("^[[=e=][.-.]x]+$", "e-x"));
+        assert!(matches("^(a|b)*c{0}$", "abab"));
+        for pattern in &["(a", "a)", "*a", "a{2,1}", "a{256}", "[a", "[b-a]", "[[:foo:]]", "a|+",
+                         "(a{255}){255}"] {
+            assert!(Regex::new(pattern).is_none(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn limits() {
+        let nested = |depth| format!("{}y{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(matches(&nested(MAX_NESTING), "y"));
+        assert!(Regex::new(&nested(1000)).is_none());
+        assert!(Regex::new(&format!("a{}", "*".repeat(1000))).is_none());
+        let branches = vec!["a"; 3000].join("|");
+        assert!(matches(&format!("^({})*b", branches), "aaab"));
+    }
+}
@@ -0,0 +1,440 @@
+//! Message translation with GNU gettext catalogs.
+//!
+//! Reads the binary `.mo` files produced by `msgfmt` and looks up translations in them. The
+//! catalogs are found the same way GNU gettext finds them, in
+//! `<dir>/<locale>/LC_MESSAGES/<domain>.mo` for the locales selected by `LC_ALL`, `LC_MESSAGES`,
+//! `LANG` and `LANGUAGE` environment variables, trying less specific variants of each locale
+//! name in turn. Locale aliases (`locale.alias`) are not supported.
+//!
+//! The `.po` source files of catalogs can also be read with `PoFile` and compiled to `.mo` files
+//! or used directly.
+
+use ::std::collections::HashMap;
+use ::std::env;
+use ::std::fs;
+use ::std::io::{Error, ErrorKind, Result};
+use ::std::path::Path;
+
+mod plural;
+mod po;
+
+pub use self::plural::{ParsePluralRuleError, PluralRule};
+pub use self::po::{ParsePoError, PoEntry, PoFile};
+
+/// The usual location of message catalogs.
+pub const LOCALE_DIR: &str = "/usr/share/locale";
+
+/// Separator of the context from the message in catalog keys.
+const CONTEXT_SEPARATOR: char = '\u{4}';
+
+/// Catalog of translated messages for one domain and language.
+///
+/// Messages that are not in the catalog are returned untranslated, so an empty catalog
+/// (`Catalog::default()`) can be used when no translations are available.
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    /// Translations, with all plural forms, by message optionally prefixed with context.
+    messages: HashMap<String, Vec<String>>,
+    /// The translation of empty message, with meta-information in `Name: value` lines.
+    header: String,
+    plural_rule: PluralRule,
+}
+
+impl Catalog {
+    /// Loads the catalog of `domain` from `dir` for the user's languages.
+    ///
+    /// See `user_languages` for how the languages are determined. Returns error of kind
+    /// `NotFound` if there is no catalog for any of them.
+    pub fn load<P: AsRef<Path>>(domain: &str, dir: P) -> Result<Catalog> {
+        Catalog::load_for(&user_languages(), domain, dir)
+    }
+
+    /// Loads the catalog of `domain` from `dir` for the first of `languages` that has one.
+    ///
+    /// Each language is a locale name, for which all variants returned by `locale_variants` are
+    /// tried. The search stops at `C` or `POSIX`, which mean no translation. Files that cannot be
+    /// read or parsed are skipped.
+    pub fn load_for<S: AsRef<str>, P: AsRef<Path>>(languages: &[S], domain: &str, dir: P)
+        -> Result<Catalog>
+    {
+        for language in languages {
+            let language = language.as_ref();
+            if language == "C" || language == "POSIX" {
+                break;
+            }
+            for variant in locale_variants(language) {
+                let path = dir.as_ref().join(variant).join("LC_MESSAGES")
+                    .join(format!("{}.mo", domain));
+                if let Ok(catalog) = Catalog::from_file(path) {
+                    return Ok(catalog);
+                }
+            }
+        }
+        Err(Error::new(ErrorKind::NotFound, "no message catalog for the languages"))
+    }
+
+    /// Reads the catalog from a `.mo` file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Catalog> {
+        Catalog::parse(&fs::read(path)?)
+    }
+
+    /// Parses the catalog from contents of a `.mo` file.
+    ///
+    /// Both byte orders are accepted and the hash table is not needed. Messages are converted to
+    /// UTF-8 from the charset given in the header, which other than UTF-8 is only supported where
+    /// `iconv` is available.
+    pub fn parse(data: &[u8]) -> Result<Catalog> {
+        let big_endian = match data.get(..4) {
+            Some(b"\xde\x12\x04\x95") => false,
+            Some(b"\x95\x04\x12\xde") => true,
+            _ => return Err(invalid_data()),
+        };
+        let word = |offset: usize| -> Result<usize> {
+            let bytes = data.get(offset..offset + 4).ok_or_else(invalid_data)?;
+            let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+            Ok(if big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) }
+               as usize)
+        };
+        // only the major revision matters, minor revisions just add optional data
+        if word(4)? >> 16 > 1 {
+            return Err(invalid_data());
+        }
+        let (count, originals, translations) = (word(8)?, word(12)?, word(16)?);
+        let string = |table: usize, index: usize| -> Result<&[u8]> {
+            let entry = index.checked_mul(8).and_then(|n| n.checked_add(table))
+                .ok_or_else(invalid_data)?;
+            let (len, offset) = (word(entry)?, word(entry + 4)?);
+            // the strings are NUL-terminated in addition to having the length
+            match data.get(offset..).and_then(|rest| rest.get(..len + 1)) {
+                Some(bytes) if bytes[len] == 0 => Ok(&bytes[..len]),
+                _ => Err(invalid_data()),
+            }
+        };
+        let mut entries = Vec::with_capacity(count.min(data.len() / 16));
+        for index in 0..count {
+            entries.push((string(originals, index)?, string(translations, index)?));
+        }
+
+        let header = entries.iter()
+            .find(|&&(original, _)| original.is_empty())
+            .map_or(&b""[..], |&(_, translation)| translation);
+        let decoder = Decoder::new(header_charset(&String::from_utf8_lossy(header))
+                                   .unwrap_or("UTF-8"))?;
+
+        let mut messages = HashMap::with_capacity(entries.len());
+        for (original, translation) in entries {
+            if original.is_empty() {
+                continue;
+            }
+            // the plural message follows the singular one, but only the singular is looked up
+            let singular = original.split(|&b| b == 0).next().unwrap_or(original);
+            let forms = translation.split(|&b| b == 0)
+                .map(|form| decoder.decode(form))
+                .collect::<Result<Vec<_>>>()?;
+            messages.insert(decoder.decode(singular)?, forms);
+        }
+        let header = decoder.decode(header)?;
+        // GNU gettext also falls back to the Germanic rule if the header one is invalid
+        let plural_rule = header_field(&header, "Plural-Forms")
+            .and_then(|plural_forms| PluralRule::parse(plural_forms).ok())
+            .unwrap_or_default();
+        Ok(Catalog { messages, header, plural_rule })
+    }
+
+    /// Returns value of a field of the catalog header, like `Language` or `Plural-Forms`.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        header_field(&self.header, name)
+    }
+
+    /// Returns the rule selecting plural forms of the translations.
+    pub fn plural_rule(&self) -> &PluralRule {
+        &self.plural_rule
+    }
+
+    /// Translates a message.
+    pub fn gettext<'a>(&'a self, msgid: &'a str) -> &'a str {
+        self.lookup(msgid, msgid, msgid, 1)
+    }
+
+    /// Translates a message with plural forms for count `n`.
+    ///
+    /// The form is selected by `plural_rule`. Without a translation `msgid` is returned for `n`
+    /// equal to 1 and `msgid_plural` otherwise.
+    pub fn ngettext<'a>(&'a self, msgid: &'a str, msgid_plural: &'a str, n: u64) -> &'a str {
+        self.lookup(msgid, msgid, msgid_plural, n)
+    }
+
+    /// Translates a message used in given context, which distinguishes messages with the same
+    /// text.
+    pub fn pgettext<'a>(&'a self, context: &str, msgid: &'a str) -> &'a str {
+        let key = format!("{}{}{}", context, CONTEXT_SEPARATOR, msgid);
+        self.lookup(&key, msgid, msgid, 1)
+    }
+
+    /// Translates a message used in given context with plural forms for count `n`.
+    pub fn npgettext<'a>(&'a self, context: &str, msgid: &'a str, msgid_plural: &'a str, n: u64)
+        -> &'a str
+    {
+        let key = format!("{}{}{}", context, CONTEXT_SEPARATOR, msgid);
+        self.lookup(&key, msgid, msgid_plural, n)
+    }
+
+    fn lookup<'a>(&'a self, key: &str, msgid: &'a str, msgid_plural: &'a str, n: u64) -> &'a str {
+        let form = self.plural_rule.form(n);
+        match self.messages.get(key).and_then(|forms| forms.get(form)) {
+            Some(translation) if !translation.is_empty() => translation,
+            _ if n == 1 => msgid,
+            _ => msgid_plural,
+        }
+    }
+}
+
+fn invalid_data() -> Error {
+    Error::new(ErrorKind::InvalidData, "invalid message catalog")
+}
+
+fn header_field<'a>(header: &'a str, name: &str) -> Option<&'a str> {
+    header.lines()
+        .filter_map(|line| {
+            let colon = line.find(':')?;
+            if line[..colon].trim().eq_ignore_ascii_case(name) {
+                Some(line[colon + 1..].trim())
+            } else {
+                None
+            }
+        })
+        .next()
+}
+
+/// Returns the charset given in the `Content-Type` field of a catalog header.
+fn header_charset(header: &str) -> Option<&str> {
+    header_field(header, "Content-Type")
+        .and_then(|content_type| content_type.split("charset=").nth(1))
+        .map(|charset| charset.split(|c: char| c == ';' || c.is_whitespace())
+             .next().unwrap_or(""))
+}
+
+/// Returns whether text in the charset is also valid UTF-8.
+fn is_utf8_charset(charset: &str) -> bool {
+    match &normalize_codeset(charset)[..] {
+        // `CHARSET` is the placeholder in templates
+        "utf8" | "ascii" | "usascii" | "ansix341968" | "charset" | "" => true,
+        _ => false,
+    }
+}
+
+/// Converts messages to UTF-8.
+enum Decoder {
+    Utf8,
+    #[cfg(target_os = "linux")]
+    IConv(::linux::IConv),
+}
+
+impl Decoder {
+    fn new(charset: &str) -> Result<Decoder> {
+        if is_utf8_charset(charset) {
+            return Ok(Decoder::Utf8);
+        }
+        #[cfg(target_os = "linux")]
+        { ::linux::IConv::new("UTF-8", charset).map(Decoder::IConv) }
+        #[cfg(not(target_os = "linux"))]
+        { Err(Error::new(ErrorKind::InvalidData, "unsupported catalog charset")) }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String> {
+        let bytes = match *self {
+            Decoder::Utf8 => bytes.to_vec(),
+            #[cfg(target_os = "linux")]
+            Decoder::IConv(ref iconv) => {
+                let mut buf = vec![0; 4 * bytes.len()];
+                let (res, read, written) = iconv.convert(bytes, &mut buf);
+                if res < 0 || read < bytes.len() {
+                    return Err(invalid_data());
+                }
+                buf.truncate(written);
+                buf
+            }
+        };
+        String::from_utf8(bytes).map_err(|_| invalid_data())
+    }
+}
+
+/// Returns the locale names to look for message catalogs for, in order of preference.
+///
+/// Like GNU gettext, this is the locale from `LC_ALL`, `LC_MESSAGES` or `LANG`, the first that is
+/// set, unless `LANGUAGE` is set to a colon-separated list of locales to use instead. `LANGUAGE`
+/// is ignored when the locale is `C`.
+pub fn user_languages() -> Vec<String> {
+    let var = |name| env::var(name).ok().filter(|value: &String| !value.is_empty());
+    let locale = var("LC_ALL")
+        .or_else(|| var("LC_MESSAGES"))
+        .or_else(|| var("LANG"))
+        .unwrap_or_else(|| "C".to_string());
+    languages(&locale, var("LANGUAGE").as_ref().map(|s| &s[..]))
+}
+
+fn languages(locale: &str, language: Option<&str>) -> Vec<String> {
+    match language {
+        Some(list) if locale != "C" => {
+            list.split(':').filter(|name| !name.is_empty()).map(str::to_string).collect()
+        }
+        _ => vec![locale.to_string()],
+    }
+}
+
+/// Returns the names to look for catalogs of a locale under, from the most specific one.
+///
+/// The locale name has the form `language[_territory][.codeset][@modifier]` and the variants
+/// leave out the parts in the same order as GNU gettext, with the modifier kept the longest, and
+/// the codeset also tried normalized, lowercase without punctuation, like `utf8`. So
+/// `de_DE.UTF-8@euro` has variants `de_DE.UTF-8@euro`, `de_DE.utf8@euro`, `de_DE@euro`,
+/// `de.UTF-8@euro`, ... and at last `de_DE` and `de`.
+pub fn locale_variants(name: &str) -> Vec<String> {
+    let (name, modifier) = split_at_char(name, '@');
+    let (name, codeset) = split_at_char(name, '.');
+    let (language, territory) = split_at_char(name, '_');
+    let normalized = codeset.map(normalize_codeset)
+        .filter(|normalized| Some(&normalized[..]) != codeset);
+    let mut variants = Vec::new();
+    for &with_modifier in &[true, false] {
+        for &with_territory in &[true, false] {
+            for &codeset in &[codeset, normalized.as_ref().map(|s| &s[..]), None] {
+                if with_modifier && modifier.is_none() || with_territory && territory.is_none() {
+                    continue;
+                }
+                let mut variant = language.to_string();
+                if with_territory {
+                    variant += "_";
+                    variant += territory.unwrap();
+                }
+                if let Some(codeset) = codeset {
+                    variant += ".";
+                    variant += codeset;
+                }
+                if with_modifier {
+                    variant += "@";
+                    variant += modifier.unwrap();
+                }
+                if !variants.contains(&variant) {
+                    variants.push(variant);
+                }
+            }
+        }
+    }
+    variants
+}
+
+fn split_at_char(s: &str, c: char) -> (&str, Option<&str>) {
+    match s.find(c) {
+        Some(index) => (&s[..index], Some(&s[index + 1..])),
+        None => (s, None),
+    }
+}
+
+/// Normalizes codeset name the way GNU gettext does: keeps only letters, in lowercase, and
+/// digits, and prefixes names consisting only of digits with `iso`.
+fn normalize_codeset(codeset: &str) -> String {
+    let normalized: String = codeset.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if !normalized.is_empty() && normalized.bytes().all(|b| b.is_ascii_digit()) {
+        format!("iso{}", normalized)
+    } else {
+        normalized
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ::std::path::PathBuf;
+
+    fn fixtures() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/locale")
+    }
+
+    #[test]
+    fn little_endian_with_hash() {
+        let catalog = Catalog::load_for(&["cs_CZ.UTF-8"], "test", fixtures()).unwrap();
+        assert_eq!(Some("cs"), catalog.header("Language"));
+        assert_eq!(Some("text/plain; charset=UTF-8"), catalog.header("content-type"));
+        assert_eq!("Ahoj", catalog.gettext("Hello"));
+        assert_eq!("Otevřít", catalog.gettext("Open"));
+        assert_eq!("Otevřené", catalog.pgettext("door", "Open"));
+        assert_eq!("Open", catalog.pgettext("window", "Open"));
+        assert_eq!("%d soubor", catalog.ngettext("%d file", "%d files", 1));
+        assert_eq!("%d soubory", catalog.ngettext("%d file", "%d files", 2));
+        assert_eq!("%d zprávy", catalog.npgettext("mail", "%d message", "%d messages", 3));
+        assert_eq!("%d zpráv", catalog.npgettext("mail", "%d message", "%d messages", 5));
+        assert_eq!("%d souborů", catalog.ngettext("%d file", "%d files", 0));
+        assert_eq!(3, catalog.plural_rule().nplurals());
+        assert_eq!("Tabulátor\t„v uvozovkách“\\", catalog.gettext("Tab\t\"quoted\"\\"));
+        assert_eq!("Víceřádková\nzpráva", catalog.gettext("Multi-line\nmessage"));
+        // fuzzy and untranslated messages are left out by msgfmt
+        assert_eq!("Fuzzy", catalog.gettext("Fuzzy"));
+        assert_eq!("Untranslated", catalog.gettext("Untranslated"));
+        assert_eq!("%d dirs", catalog.ngettext("%d dir", "%d dirs", 0));
+    }
+
+    #[test]
+    fn big_endian_without_hash() {
+        let catalog = Catalog::load_for(&["de_DE"], "test", fixtures()).unwrap();
+        assert_eq!("Hallo", catalog.gettext("Hello"));
+        assert_eq!(&PluralRule::germanic(), catalog.plural_rule());
+        assert_eq!("Offen", catalog.pgettext("door", "Open"));
+        assert_eq!("%d Dateien", catalog.ngettext("%d file", "%d files", 0));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn charset_conversion() {
+        let catalog = Catalog::load_for(&["sk_SK"], "test", fixtures()).unwrap();
+        assert_eq!("Dobrý deň", catalog.gettext("Hello"));
+    }
+
+    #[test]
+    fn catalog_search() {
+        let load = |languages: &[&str]| {
+            Catalog::load_for(languages, "test", fixtures()).map(|c| c.gettext("Hello").to_string())
+        };
+        assert_eq!("Ahoj", load(&["pl_PL", "cs_CZ.UTF-8@latin", "de_DE"]).unwrap());
+        assert_eq!("Hallo", load(&["de_DE.ISO-8859-1@euro"]).unwrap());
+        assert_eq!(ErrorKind::NotFound, load(&["de_AT", "C", "cs"]).unwrap_err().kind());
+        assert_eq!(ErrorKind::NotFound, load(&["POSIX", "cs"]).unwrap_err().kind());
+        assert!(Catalog::load_for(&["cs"], "missing", fixtures()).is_err());
+        assert_eq!("Hello", Catalog::default().gettext("Hello"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let data = fs::read(fixtures().join("cs/LC_MESSAGES/test.mo")).unwrap();
+        let kind = |data: &[u8]| Catalog::parse(data).unwrap_err().kind();
+        assert_eq!(ErrorKind::InvalidData, kind(&data[..data.len() - 1]));
+        assert_eq!(ErrorKind::InvalidData, kind(&data[..20]));
+        assert_eq!(ErrorKind::InvalidData, kind(b"\xde\x12\x04\x96"));
+        let mut revision = data.clone();
+        revision[6] = 2;
+        assert_eq!(ErrorKind::InvalidData, kind(&revision));
+    }
+
+    #[test]
+    fn language_list() {
+        assert_eq!(vec!["cs_CZ.UTF-8"], languages("cs_CZ.UTF-8", None));
+        assert_eq!(vec!["sk", "cs"], languages("cs_CZ.UTF-8", Some("sk::cs")));
+        assert_eq!(vec!["C"], languages("C", Some("sk:cs")));
+    }
+
+    #[test]
+    fn variants() {
+        assert_eq!(vec!["de_DE.UTF-8@euro", "de_DE.utf8@euro", "de_DE@euro", "de.UTF-8@euro",
+                        "de.utf8@euro", "de@euro", "de_DE.UTF-8", "de_DE.utf8", "de_DE",
+                        "de.UTF-8", "de.utf8", "de"],
+                   locale_variants("de_DE.UTF-8@euro"));
+        assert_eq!(vec!["sr@latin", "sr"], locale_variants("sr@latin"));
+        assert_eq!(vec!["cs_CZ.utf8", "cs_CZ", "cs.utf8", "cs"], locale_variants("cs_CZ.utf8"));
+        assert_eq!(vec!["pl_PL.88592", "pl_PL.iso88592", "pl_PL", "pl.88592", "pl.iso88592", "pl"],
+                   locale_variants("pl_PL.88592"));
+        assert_eq!(vec!["cs"], locale_variants("cs"));
+    }
+}
@@ -0,0 +1,448 @@
+//! Plural form selection by the `Plural-Forms` expressions of gettext catalogs.
+
+use ::std::error::Error;
+use ::std::fmt::{self, Display};
+
+/// Maximum number of tokens in an expression, which also bounds depth of the expression tree.
+const MAX_TOKENS: usize = 256;
+
+/// Maximum nesting of parentheses, negations and conditionals.
+const MAX_NESTING: usize = 32;
+
+/// Rule selecting plural form of a message for a count.
+///
+/// The rule is a C-like expression over the count `n`, as given in the `Plural-Forms` header of
+/// gettext catalogs, like `nplurals=3; plural=(n==1) ? 0 : (n>=2 && n<=4) ? 1 : 2;` for Czech. It
+/// supports the integer operators `?:`, `||`, `&&`, `==`, `!=`, `<`, `>`, `<=`, `>=`, `+`, `-`,
+/// `*`, `/`, `%` and `!` and is evaluated with unsigned arithmetic, like in GNU gettext.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluralRule {
+    nplurals: usize,
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    N,
+    Number(u64),
+    Not(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    Greater,
+    LessOrEqual,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl PluralRule {
+    /// Parses the rule from the value of `Plural-Forms` header, like
+    /// `nplurals=2; plural=(n != 1);`.
+    pub fn parse(plural_forms: &str) -> Result<PluralRule, ParsePluralRuleError> {
+        let mut nplurals = None;
+        let mut expression = None;
+        let mut offset = 0;
+        for part in plural_forms.split(';') {
+            let start = offset + part.len() - part.trim_start().len();
+            offset += part.len() + 1;
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+            let equals = part.find('=')
+                .ok_or_else(|| ParsePluralRuleError::new("expected `=`", start))?;
+            let value_start = start + equals + 1;
+            match part[..equals].trim() {
+                "nplurals" => {
+                    nplurals = Some(part[equals + 1..].trim().parse::<usize>().ok()
+                        .filter(|&nplurals| nplurals > 0)
+                        .ok_or_else(|| ParsePluralRuleError::new("invalid nplurals",
+                                                                 value_start))?);
+                }
+                "plural" => expression = Some((&part[equals + 1..], value_start)),
+                _ => return Err(ParsePluralRuleError::new("unknown field", start)),
+            }
+        }
+        let nplurals = nplurals
+            .ok_or_else(|| ParsePluralRuleError::new("missing nplurals", plural_forms.len()))?;
+        let (expression, start) = expression
+            .ok_or_else(|| ParsePluralRuleError::new("missing plural", plural_forms.len()))?;
+        PluralRule::from_expression(nplurals, expression)
+            .map_err(|e| ParsePluralRuleError::new(e.message, start + e.position))
+    }
+
+    /// Constructs the rule from number of plural forms and the expression selecting one, like
+    /// `n != 1`.
+    pub fn from_expression(nplurals: usize, expression: &str)
+        -> Result<PluralRule, ParsePluralRuleError>
+    {
+        if nplurals == 0 {
+            return Err(ParsePluralRuleError::new("invalid nplurals", 0));
+        }
+        let mut parser = Parser { tokens: tokenize(expression)?, pos: 0, nesting: 0 };
+        let expr = parser.conditional()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(PluralRule { nplurals, expr }),
+            Some(&(_, position)) => Err(ParsePluralRuleError::new("unexpected token", position)),
+        }
+    }
+
+    /// Returns the rule of English and other Germanic languages, one form for 1 and another for
+    /// all other counts. GNU gettext uses this if a catalog does not specify any.
+    pub fn germanic() -> PluralRule {
+        PluralRule {
+            nplurals: 2,
+            expr: Expr::Binary(Op::NotEqual, Box::new(Expr::N), Box::new(Expr::Number(1))),
+        }
+    }
+
+    /// Returns number of plural forms.
+    pub fn nplurals(&self) -> usize {
+        self.nplurals
+    }
+
+    /// Returns index of the plural form to use for count `n`.
+    ///
+    /// Like in GNU gettext, the first form is used when the expression evaluates out of range or
+    /// divides by zero.
+    pub fn form(&self, n: u64) -> usize {
+        match eval(&self.expr, n) {
+            Some(form) if form < self.nplurals as u64 => form as usize,
+            _ => 0,
+        }
+    }
+}
+
+impl Default for PluralRule {
+    fn default() -> PluralRule {
+        PluralRule::germanic()
+    }
+}
+
+fn eval(expr: &Expr, n: u64) -> Option<u64> {
+    Some(match *expr {
+        Expr::N => n,
+        Expr::Number(value) => value,
+        Expr::Not(ref operand) => (eval(operand, n)? == 0) as u64,
+        Expr::Conditional(ref condition, ref then, ref otherwise) => {
+            if eval(condition, n)? != 0 { eval(then, n)? } else { eval(otherwise, n)? }
+        }
+        Expr::Binary(op, ref left, ref right) => {
+            let left = eval(left, n)?;
+            // the logical operators short-circuit
+            match op {
+                Op::Or if left != 0 => return Some(1),
+                Op::And if left == 0 => return Some(0),
+                _ => {}
+            }
+            let right = eval(right, n)?;
+            match op {
+                Op::Or | Op::And => (right != 0) as u64,
+                Op::Equal => (left == right) as u64,
+                Op::NotEqual => (left != right) as u64,
+                Op::Less => (left < right) as u64,
+                Op::Greater => (left > right) as u64,
+                Op::LessOrEqual => (left <= right) as u64,
+                Op::GreaterOrEqual => (left >= right) as u64,
+                Op::Add => left.wrapping_add(right),
+                Op::Subtract => left.wrapping_sub(right),
+                Op::Multiply => left.wrapping_mul(right),
+                Op::Divide => left.checked_div(right)?,
+                Op::Remainder => left.checked_rem(right)?,
+            }
+        }
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    N,
+    Number(u64),
+    Op(Op),
+    Not,
+    Question,
+    Colon,
+    Open,
+    Close,
+}
+
+/// Splits the expression to tokens with their byte positions.
+fn tokenize(expression: &str) -> Result<Vec<(Token, usize)>, ParsePluralRuleError> {
+    let bytes = expression.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let start = pos;
+        let next = bytes.get(pos + 1).cloned();
+        pos += 1;
+        let token = match bytes[start] {
+            b' ' | b'\t' | b'\n' | b'\r' => continue,
+            b'n' => Token::N,
+            b'0'..=b'9' => {
+                let mut value: u64 = (bytes[start] - b'0') as u64;
+                while let Some(digit) = bytes.get(pos).filter(|b| b.is_ascii_digit()) {
+                    value = value.checked_mul(10).and_then(|v| v.checked_add((digit - b'0') as u64))
+                        .ok_or_else(|| ParsePluralRuleError::new("number too large", start))?;
+                    pos += 1;
+                }
+                Token::Number(value)
+            }
+            b'|' if next == Some(b'|') => Token::Op(Op::Or),
+            b'&' if next == Some(b'&') => Token::Op(Op::And),
+            b'=' if next == Some(b'=') => Token::Op(Op::Equal),
+            b'!' if next == Some(b'=') => Token::Op(Op::NotEqual),
+            b'<' if next == Some(b'=') => Token::Op(Op::LessOrEqual),
+            b'>' if next == Some(b'=') => Token::Op(Op::GreaterOrEqual),
+            b'<' => Token::Op(Op::Less),
+            b'>' => Token::Op(Op::Greater),
+            b'+' => Token::Op(Op::Add),
+            b'-' => Token::Op(Op::Subtract),
+            b'*' => Token::Op(Op::Multiply),
+            b'/' => Token::Op(Op::Divide),
+            b'%' => Token::Op(Op::Remainder),
+            b'!' => Token::Not,
+            b'?' => Token::Question,
+            b':' => Token::Colon,
+            b'(' => Token::Open,
+            b')' => Token::Close,
+            _ => return Err(ParsePluralRuleError::new("unexpected character", start)),
+        };
+        // the two-character operators
+        if let Token::Op(Op::Or) | Token::Op(Op::And) | Token::Op(Op::Equal)
+            | Token::Op(Op::NotEqual) | Token::Op(Op::LessOrEqual)
+            | Token::Op(Op::GreaterOrEqual) = token
+        {
+            pos += 1;
+        }
+        if tokens.len() == MAX_TOKENS {
+            return Err(ParsePluralRuleError::new("expression too long", start));
+        }
+        tokens.push((token, start));
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser with the precedence of C operators.
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    nesting: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.pos).map(|&(token, _)| token)
+    }
+
+    fn error(&self, message: &'static str) -> ParsePluralRuleError {
+        let position = self.tokens.get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map_or(0, |&(_, position)| position);
+        ParsePluralRuleError::new(message, position)
+    }
+
+    fn enter(&mut self) -> Result<(), ParsePluralRuleError> {
+        self.nesting += 1;
+        if self.nesting > MAX_NESTING {
+            return Err(self.error("expression nested too deep"));
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, token: Token, message: &'static str) -> Result<(), ParsePluralRuleError> {
+        if self.peek() != Some(token) {
+            return Err(self.error(message));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn conditional(&mut self) -> Result<Expr, ParsePluralRuleError> {
+        let condition = self.binary(0)?;
+        if self.peek() != Some(Token::Question) {
+            return Ok(condition);
+        }
+        self.pos += 1;
+        self.enter()?;
+        let then = self.conditional()?;
+        self.expect(Token::Colon, "expected `:`")?;
+        let otherwise = self.conditional()?;
+        self.nesting -= 1;
+        Ok(Expr::Conditional(Box::new(condition), Box::new(then), Box::new(otherwise)))
+    }
+
+    /// Parses binary operators of given precedence level and higher.
+    fn binary(&mut self, level: usize) -> Result<Expr, ParsePluralRuleError> {
+        const LEVELS: &[&[Op]] = &[
+            &[Op::Or],
+            &[Op::And],
+            &[Op::Equal, Op::NotEqual],
+            &[Op::Less, Op::Greater, Op::LessOrEqual, Op::GreaterOrEqual],
+            &[Op::Add, Op::Subtract],
+            &[Op::Multiply, Op::Divide, Op::Remainder],
+        ];
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        while let Some(Token::Op(op)) = self.peek() {
+            if !LEVELS[level].contains(&op) {
+                break;
+            }
+            self.pos += 1;
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParsePluralRuleError> {
+        let token = self.peek().ok_or_else(|| self.error("unexpected end of expression"))?;
+        self.pos += 1;
+        match token {
+            Token::N => Ok(Expr::N),
+            Token::Number(value) => Ok(Expr::Number(value)),
+            Token::Not => {
+                self.enter()?;
+                let operand = self.unary()?;
+                self.nesting -= 1;
+                Ok(Expr::Not(Box::new(operand)))
+            }
+            Token::Open => {
+                self.enter()?;
+                let expr = self.conditional()?;
+                self.expect(Token::Close, "expected `)`")?;
+                self.nesting -= 1;
+                Ok(expr)
+            }
+            _ => {
+                self.pos -= 1;
+                Err(self.error("expected operand"))
+            }
+        }
+    }
+}
+
+/// Error returned from parsing `PluralRule`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePluralRuleError {
+    message: &'static str,
+    position: usize,
+}
+
+impl ParsePluralRuleError {
+    fn new(message: &'static str, position: usize) -> Self {
+        ParsePluralRuleError { message, position }
+    }
+
+    /// Returns byte offset in the input at which the problem was detected.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Display for ParsePluralRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl Error for ParsePluralRuleError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn forms(rule: &PluralRule, counts: &[u64]) -> Vec<usize> {
+        counts.iter().map(|&n| rule.form(n)).collect()
+    }
+
+    #[test]
+    fn languages() {
+        let czech = PluralRule::parse("nplurals=3; plural=(n==1) ? 0 : (n>=2 && n<=4) ? 1 : 2;")
+            .unwrap();
+        assert_eq!(3, czech.nplurals());
+        assert_eq!(vec![2, 0, 1, 1, 2, 2], forms(&czech, &[0, 1, 2, 4, 5, 22]));
+
+        let polish = PluralRule::parse("nplurals=3; plural=(n==1 ? 0 : \
+                                        n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);")
+            .unwrap();
+        assert_eq!(vec![2, 0, 1, 2, 2, 1, 2], forms(&polish, &[0, 1, 2, 5, 12, 22, 25]));
+
+        let russian = PluralRule::parse("nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : \
+                                         n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);")
+            .unwrap();
+        assert_eq!(vec![2, 0, 1, 2, 2, 0, 1, 2], forms(&russian, &[0, 1, 2, 5, 11, 21, 22, 111]));
+
+        let arabic = PluralRule::parse("nplurals=6; plural=n==0 ? 0 : n==1 ? 1 : n==2 ? 2 : \
+                                        n%100>=3 && n%100<=10 ? 3 : n%100>=11 ? 4 : 5;")
+            .unwrap();
+        assert_eq!(vec![0, 1, 2, 3, 4, 5, 3], forms(&arabic, &[0, 1, 2, 3, 11, 100, 103]));
+
+        let japanese = PluralRule::parse("nplurals=1; plural=0;").unwrap();
+        assert_eq!(vec![0, 0], forms(&japanese, &[1, 2]));
+
+        assert_eq!(PluralRule::germanic(), PluralRule::parse("nplurals=2; plural=n != 1").unwrap());
+        assert_eq!(vec![1, 0, 1], forms(&PluralRule::default(), &[0, 1, 2]));
+    }
+
+    #[test]
+    fn evaluation() {
+        let form = |expression, n| PluralRule::from_expression(100, expression).unwrap().form(n);
+        assert_eq!(7, form("1 + 2 * 3", 0));
+        assert_eq!(9, form("(1 + 2) * 3", 0));
+        assert_eq!(1, form("10 - 3 - 6", 0));
+        assert_eq!(2, form("n / 3 % 4", 30));
+        assert_eq!(1, form("!(n > 5) == 1", 2));
+        assert_eq!(0, form("!!n", 0));
+        assert_eq!(4, form("n ? n ? 4 : 3 : 2", 1));
+        // unsigned arithmetic
+        assert_eq!(1, form("0 - 1 > 0", 0));
+        // out of range and division by zero give the first form
+        assert_eq!(0, form("n", 100));
+        assert_eq!(0, form("5 / n", 0));
+        assert_eq!(0, form("5 % n", 0));
+        // short-circuit evaluation avoids the division
+        assert_eq!(1, form("n == 0 || 5 / n", 0));
+        assert_eq!(0, form("n != 0 && 5 / n", 0));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |plural_forms| PluralRule::parse(plural_forms).unwrap_err().position();
+        assert_eq!(21, error("nplurals=2; plural=n !! 1;"));
+        assert_eq!(9, error("nplurals=0; plural=0;"));
+        assert_eq!(9, error("nplurals=x; plural=0;"));
+        assert_eq!(12, error("nplurals=2; plural"));
+        assert_eq!(11, error("plural=n!=1"));
+        assert_eq!(19, error("nplurals=2;plural=(n"));
+        assert_eq!(23, error("nplurals=2; plural=n ? 1;"));
+        assert_eq!(21, error("nplurals=2; plural=n 1;"));
+        assert_eq!(19, error("nplurals=2; plural=99999999999999999999;"));
+        assert_eq!(12, error("nplurals=2; size=3;"));
+        assert_eq!(26, error("nplurals=2; plural=n == 1 x;"));
+
+        let nested = format!("{}n{}", "(".repeat(1000), ")".repeat(1000));
+        assert!(PluralRule::from_expression(2, &nested).is_err());
+        let negated = format!("{}n", "!".repeat(100));
+        assert!(PluralRule::from_expression(2, &negated).is_err());
+        let long = vec!["n"; 1000].join("+");
+        assert!(PluralRule::from_expression(2, &long).is_err());
+        let conditional = "n ? ".repeat(40) + "1" + &" : 0".repeat(40);
+        assert!(PluralRule::from_expression(2, &conditional).is_err());
+        assert!(PluralRule::from_expression(2, "").is_err());
+        assert!(PluralRule::from_expression(0, "0").is_err());
+    }
+}
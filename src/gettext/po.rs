@@ -0,0 +1,552 @@
+//! Reading of `.po` translation sources and compiling them to `.mo` catalogs.
+
+use ::std::error::Error;
+use ::std::fmt::{self, Display};
+use ::std::fs;
+use ::std::io::{self, ErrorKind, Write};
+use ::std::path::Path;
+use ::std::str;
+
+use super::{header_charset, header_field, invalid_data, is_utf8_charset, Catalog, Decoder,
+            CONTEXT_SEPARATOR};
+
+/// Message of a `.po` file with its translation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoEntry {
+    /// Context distinguishing messages with the same text, from `msgctxt`.
+    pub context: Option<String>,
+    /// The untranslated message, empty for the header entry.
+    pub msgid: String,
+    /// The untranslated plural message, from `msgid_plural`.
+    pub msgid_plural: Option<String>,
+    /// The translation, or one for each plural form if there is `msgid_plural`. Empty strings
+    /// mean the message is not translated.
+    pub msgstr: Vec<String>,
+    /// Flags from `#,` comments, like `fuzzy` or `c-format`.
+    pub flags: Vec<String>,
+    /// Other comment lines, including the `#` and the character after it, like
+    /// `#: src/main.rs:10`.
+    pub comments: Vec<String>,
+    /// Whether the entry is commented out with `#~` because the message is no longer used.
+    pub obsolete: bool,
+}
+
+impl PoEntry {
+    /// Returns whether the translation is marked as needing review by the `fuzzy` flag.
+    pub fn is_fuzzy(&self) -> bool {
+        self.flags.iter().any(|flag| flag == "fuzzy")
+    }
+
+    /// Returns whether this is the header entry, the translation of empty message without
+    /// context.
+    pub fn is_header(&self) -> bool {
+        self.context.is_none() && self.msgid.is_empty()
+    }
+
+    /// Returns whether the message is translated. Like in `msgfmt`, only the first plural form
+    /// matters.
+    pub fn is_translated(&self) -> bool {
+        self.msgstr.first().filter(|msgstr| !msgstr.is_empty()).is_some()
+    }
+}
+
+/// Contents of a `.po` file, the source of a message catalog.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoFile {
+    /// The entries in order of the file, including the header and obsolete entries.
+    pub entries: Vec<PoEntry>,
+}
+
+impl PoFile {
+    /// Reads a `.po` file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<PoFile> {
+        PoFile::from_bytes(&fs::read(path)?)
+    }
+
+    /// Parses contents of a `.po` file, converting it to UTF-8 from the charset given in the
+    /// header.
+    ///
+    /// Charsets other than UTF-8 are only supported where `iconv` is available. Syntax errors are
+    /// returned as errors of kind `InvalidData` wrapping `ParsePoError`.
+    pub fn from_bytes(data: &[u8]) -> io::Result<PoFile> {
+        let syntax_error = |e: ParsePoError| io::Error::new(ErrorKind::InvalidData, e);
+        // the charset is in ASCII, so the header can be read before it is known
+        let po = PoFile::parse(&String::from_utf8_lossy(data)).map_err(syntax_error)?;
+        match po.header_entry().and_then(|entry| header_charset(&entry.msgstr[0])) {
+            Some(charset) if !is_utf8_charset(charset) => {
+                PoFile::parse(&Decoder::new(charset)?.decode(data)?).map_err(syntax_error)
+            }
+            _ if str::from_utf8(data).is_err() => Err(invalid_data()),
+            _ => Ok(po),
+        }
+    }
+
+    /// Parses contents of a `.po` file.
+    ///
+    /// Both the entries that are in use and the obsolete ones, commented out with `#~`, are
+    /// read. The strings can be split to several lines and use the C escape sequences.
+    pub fn parse(text: &str) -> Result<PoFile, ParsePoError> {
+        let mut parser = Parser { entries: Vec::new(), entry: PoEntry::default(), field: None };
+        let mut number = 0;
+        for line in text.lines() {
+            number += 1;
+            parser.line(line.trim(), number)?;
+        }
+        match parser.field {
+            None | Some(Field::Msgstr(_)) => parser.end_entry(),
+            Some(_) => return Err(ParsePoError::new("missing msgstr", number)),
+        }
+        Ok(PoFile { entries: parser.entries })
+    }
+
+    /// Returns value of a field of the header, like `Language` or `Plural-Forms`.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.header_entry().and_then(|entry| header_field(&entry.msgstr[0], name))
+    }
+
+    fn header_entry(&self) -> Option<&PoEntry> {
+        self.entries.iter()
+            .find(|entry| entry.is_header() && !entry.obsolete && entry.is_translated())
+    }
+
+    /// Writes the catalog in the binary `.mo` format.
+    ///
+    /// Like `msgfmt`, this leaves out obsolete and untranslated messages and fuzzy ones except
+    /// the header, and writes little-endian file with the hash table. The messages are in UTF-8,
+    /// so charset in the header is changed to UTF-8 too.
+    pub fn write_mo<W: Write>(&self, mut out: W) -> io::Result<()> {
+        let mut messages: Vec<(String, String)> = self.entries.iter()
+            .filter(|entry| {
+                !entry.obsolete && entry.is_translated() && (entry.is_header() || !entry.is_fuzzy())
+            })
+            .map(|entry| {
+                let mut original = match entry.context {
+                    Some(ref context) => format!("{}{}{}", context, CONTEXT_SEPARATOR, entry.msgid),
+                    None => entry.msgid.clone(),
+                };
+                if let Some(ref msgid_plural) = entry.msgid_plural {
+                    original.push('\0');
+                    original += msgid_plural;
+                }
+                let translation = if entry.is_header() {
+                    utf8_header(&entry.msgstr[0])
+                } else {
+                    entry.msgstr.join("\0")
+                };
+                (original, translation)
+            })
+            .collect();
+        messages.sort();
+
+        let count = messages.len();
+        let hash_size = hash_table_size(count);
+        let strings_offset = 28 + 16 * count + 4 * hash_size;
+        let size = messages.iter()
+            .fold(strings_offset, |size, (original, translation)| {
+                size + original.len() + translation.len() + 2
+            });
+        if size > u32::MAX as usize {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "message catalog too large"));
+        }
+
+        let mut data = Vec::with_capacity(size);
+        {
+            let mut word = |value: usize| data.extend_from_slice(&(value as u32).to_le_bytes());
+            for &value in &[0x9504_12de, 0, count, 28, 28 + 8 * count, hash_size, 28 + 16 * count] {
+                word(value);
+            }
+            let mut offset = strings_offset;
+            for (original, _) in &messages {
+                word(original.len());
+                word(offset);
+                offset += original.len() + 1;
+            }
+            for (_, translation) in &messages {
+                word(translation.len());
+                word(offset);
+                offset += translation.len() + 1;
+            }
+            for index in hash_table(&messages, hash_size) {
+                word(index);
+            }
+        }
+        for string in messages.iter().map(|m| &m.0).chain(messages.iter().map(|m| &m.1)) {
+            data.extend_from_slice(string.as_bytes());
+            data.push(0);
+        }
+        out.write_all(&data)
+    }
+
+    /// Returns the catalog the `.mo` file written by `write_mo` would contain.
+    pub fn to_catalog(&self) -> io::Result<Catalog> {
+        let mut data = Vec::new();
+        self.write_mo(&mut data)?;
+        Catalog::parse(&data)
+    }
+}
+
+/// Returns the header with charset changed to UTF-8.
+fn utf8_header(header: &str) -> String {
+    match header_charset(header) {
+        Some(charset) if !is_utf8_charset(charset) => {
+            header.split('\n')
+                .map(|line| if header_field(line, "Content-Type").is_some() {
+                    line.replacen(&format!("charset={}", charset), "charset=UTF-8", 1)
+                } else {
+                    line.to_string()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        _ => header.to_string(),
+    }
+}
+
+/// Returns size of the hash table for `count` messages, the same as `msgfmt` uses.
+fn hash_table_size(count: usize) -> usize {
+    // sizes are odd, so only odd divisors are needed
+    let is_prime = |n: usize| {
+        (3..).step_by(2).take_while(|divisor| divisor * divisor <= n)
+            .all(|divisor| n / divisor * divisor != n)
+    };
+    let mut size = (count * 4 / 3).max(10) | 1;
+    while !is_prime(size) {
+        size += 2;
+    }
+    size
+}
+
+/// Returns the hash table of the messages, with 1-based indices of the messages and zeros for
+/// empty slots. Collisions are resolved by double hashing.
+fn hash_table(messages: &[(String, String)], size: usize) -> Vec<usize> {
+    let mut table = vec![0; size];
+    for (index, (original, _)) in messages.iter().enumerate() {
+        // the lookup is by the singular message only
+        let hash = hash_string(original.split('\0').next().unwrap_or("")) as usize;
+        let step = 1 + hash % (size - 2);
+        let mut slot = hash % size;
+        while table[slot] != 0 {
+            slot = if slot >= size - step { slot + step - size } else { slot + step };
+        }
+        table[slot] = index + 1;
+    }
+    table
+}
+
+/// The hash function of GNU gettext, the ELF hash.
+fn hash_string(s: &str) -> u32 {
+    s.bytes().fold(0u32, |hash, byte| {
+        let hash = (hash << 4).wrapping_add(u32::from(byte));
+        let high = hash & 0xf000_0000;
+        if high != 0 { hash ^ high >> 24 ^ high } else { hash }
+    })
+}
+
+/// Error of parsing a `.po` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsePoError {
+    message: &'static str,
+    line: usize,
+}
+
+impl ParsePoError {
+    fn new(message: &'static str, line: usize) -> Self {
+        ParsePoError { message, line }
+    }
+
+    /// Returns number of the line at which the problem was detected, starting with 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl Display for ParsePoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at line {}", self.message, self.line)
+    }
+}
+
+impl Error for ParsePoError {}
+
+/// Field of an entry that continuation strings are appended to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Context,
+    Msgid,
+    MsgidPlural,
+    Msgstr(usize),
+}
+
+struct Parser {
+    entries: Vec<PoEntry>,
+    entry: PoEntry,
+    /// The last field started, `None` before `msgctxt` or `msgid` of the entry.
+    field: Option<Field>,
+}
+
+impl Parser {
+    fn line(&mut self, line: &str, number: usize) -> Result<(), ParsePoError> {
+        let (line, obsolete) = match line.strip_prefix("#~") {
+            // previous messages of obsolete entries are just comments
+            Some(rest) if !rest.starts_with('|') => (rest.trim_start(), true),
+            _ => (line, false),
+        };
+        if line.is_empty() {
+            return Ok(());
+        }
+        if line.starts_with('#') {
+            if let Some(Field::Msgstr(_)) = self.field {
+                self.end_entry();
+            }
+            match line.strip_prefix("#,") {
+                Some(flags) => {
+                    self.entry.flags.extend(flags.split(',')
+                        .map(str::trim)
+                        .filter(|flag| !flag.is_empty())
+                        .map(str::to_string));
+                }
+                None => self.entry.comments.push(line.to_string()),
+            }
+            return Ok(());
+        }
+
+        let error = |message| ParsePoError::new(message, number);
+        if line.starts_with('"') {
+            let string = unquote(line).ok_or_else(|| error("invalid string"))?;
+            let field = self.field.ok_or_else(|| error("unexpected string"))?;
+            self.field_mut(field).push_str(&string);
+            return Ok(());
+        }
+        let keyword_end = line.find(|c: char| c == '"' || c.is_whitespace()).unwrap_or(line.len());
+        let (keyword, value) = line.split_at(keyword_end);
+        let value = unquote(value.trim_start()).ok_or_else(|| error("invalid string"))?;
+        if keyword == "msgctxt" || keyword == "msgid" {
+            if let Some(Field::Msgstr(_)) = self.field {
+                self.end_entry();
+            }
+            self.entry.obsolete = obsolete;
+        }
+        let has_plural = self.entry.msgid_plural.is_some();
+        let field = match (keyword, self.field) {
+            ("msgctxt", None) => Field::Context,
+            ("msgid", None) | ("msgid", Some(Field::Context)) => Field::Msgid,
+            ("msgid_plural", Some(Field::Msgid)) => Field::MsgidPlural,
+            ("msgstr", Some(Field::Msgid)) => Field::Msgstr(0),
+            (_, field) if keyword.starts_with("msgstr[") && keyword.ends_with(']') => {
+                let index = keyword[7..keyword.len() - 1].parse::<usize>()
+                    .map_err(|_| error("invalid plural form index"))?;
+                match field {
+                    Some(Field::MsgidPlural) if index == 0 => Field::Msgstr(0),
+                    Some(Field::Msgstr(last)) if has_plural && index == last + 1 => {
+                        Field::Msgstr(index)
+                    }
+                    _ => return Err(error("unexpected msgstr")),
+                }
+            }
+            ("msgctxt", _) | ("msgid", _) | ("msgid_plural", _) | ("msgstr", _) => {
+                return Err(error(if keyword == "msgstr" {
+                    "unexpected msgstr"
+                } else {
+                    "missing msgstr"
+                }));
+            }
+            _ => return Err(error("unknown keyword")),
+        };
+        match field {
+            Field::Context => self.entry.context = Some(String::new()),
+            Field::MsgidPlural => self.entry.msgid_plural = Some(String::new()),
+            Field::Msgstr(_) => self.entry.msgstr.push(String::new()),
+            Field::Msgid => (),
+        }
+        self.field = Some(field);
+        self.field_mut(field).push_str(&value);
+        Ok(())
+    }
+
+    fn field_mut(&mut self, field: Field) -> &mut String {
+        match field {
+            Field::Context => self.entry.context.get_or_insert_with(String::new),
+            Field::Msgid => &mut self.entry.msgid,
+            Field::MsgidPlural => self.entry.msgid_plural.get_or_insert_with(String::new),
+            Field::Msgstr(index) => &mut self.entry.msgstr[index],
+        }
+    }
+
+    /// Adds the current entry to the entries, if it has a message. Comments not followed by any
+    /// message are dropped.
+    fn end_entry(&mut self) {
+        let entry = ::std::mem::take(&mut self.entry);
+        if self.field.is_some() {
+            self.entries.push(entry);
+        }
+        self.field = None;
+    }
+}
+
+/// Returns contents of a quoted string with the escape sequences replaced.
+fn unquote(s: &str) -> Option<String> {
+    let inner = s.strip_prefix('"')?.strip_suffix('"')?;
+    let mut result = String::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return None,
+            '\\' => {
+                let c = match chars.next()? {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    'a' => '\u{7}',
+                    'b' => '\u{8}',
+                    'f' => '\u{c}',
+                    'v' => '\u{b}',
+                    c @ '\\' | c @ '"' | c @ '\'' | c @ '?' => c,
+                    c @ '0'..='7' => {
+                        let mut value = c.to_digit(8)?;
+                        for _ in 0..2 {
+                            match chars.peek().and_then(|c| c.to_digit(8)) {
+                                Some(digit) => value = value * 8 + digit,
+                                None => break,
+                            }
+                            chars.next();
+                        }
+                        ::std::char::from_u32(value)?
+                    }
+                    'x' => {
+                        let mut value = 0;
+                        let mut digits = 0;
+                        while let Some(digit) = chars.peek().and_then(|c| c.to_digit(16)) {
+                            value = value * 16 + digit;
+                            digits += 1;
+                            if digits > 2 {
+                                return None;
+                            }
+                            chars.next();
+                        }
+                        if digits == 0 {
+                            return None;
+                        }
+                        ::std::char::from_u32(value)?
+                    }
+                    _ => return None,
+                };
+                result.push(c);
+            }
+            c => result.push(c),
+        }
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ::std::path::PathBuf;
+
+    fn fixtures() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests")
+    }
+
+    fn entry(msgid: &str, msgstr: &str) -> PoEntry {
+        PoEntry { msgid: msgid.to_string(), msgstr: vec![msgstr.to_string()], ..PoEntry::default() }
+    }
+
+    #[test]
+    fn parse_file() {
+        let po = PoFile::from_file(fixtures().join("po/cs.po")).unwrap();
+        assert_eq!(11, po.entries.len());
+        assert_eq!(Some("cs"), po.header("Language"));
+        assert!(po.entries[0].is_header());
+        assert_eq!(vec!["# Czech translations of the test domain.".to_string(),
+                        "# Compiled with `msgfmt -o ../locale/cs/LC_MESSAGES/test.mo cs.po`."
+                            .to_string()],
+                   po.entries[0].comments);
+        assert_eq!(entry("Hello", "Ahoj"), po.entries[1]);
+        assert_eq!(vec!["#: src/main.rs:10".to_string()], po.entries[2].comments);
+        assert_eq!(Some("door".to_string()), po.entries[3].context);
+        assert_eq!("Otevřené", po.entries[3].msgstr[0]);
+
+        let plural = &po.entries[4];
+        assert_eq!(vec!["c-format".to_string()], plural.flags);
+        assert_eq!("%d file", plural.msgid);
+        assert_eq!(Some("%d files".to_string()), plural.msgid_plural);
+        assert_eq!(vec!["%d soubor", "%d soubory", "%d souborů"], plural.msgstr);
+        assert_eq!(Some("mail".to_string()), po.entries[5].context);
+
+        assert_eq!(entry("Tab\t\"quoted\"\\", "Tabulátor\t„v uvozovkách“\\"), po.entries[6]);
+        assert_eq!(entry("Multi-line\nmessage", "Víceřádková\nzpráva"), po.entries[7]);
+        assert!(po.entries[8].is_fuzzy());
+        assert!(po.entries[8].is_translated());
+        assert!(!po.entries[9].is_translated());
+        assert!(po.entries[10].obsolete);
+        assert_eq!("Zastaralé", po.entries[10].msgstr[0]);
+    }
+
+    #[test]
+    fn compile_like_msgfmt() {
+        let po = PoFile::from_file(fixtures().join("po/cs.po")).unwrap();
+        let mut data = Vec::new();
+        po.write_mo(&mut data).unwrap();
+        assert_eq!(fs::read(fixtures().join("locale/cs/LC_MESSAGES/test.mo")).unwrap(), data);
+
+        let catalog = po.to_catalog().unwrap();
+        assert_eq!("Otevřené", catalog.pgettext("door", "Open"));
+        assert_eq!("%d zprávy", catalog.npgettext("mail", "%d message", "%d messages", 3));
+        assert_eq!("Fuzzy", catalog.gettext("Fuzzy"));
+        assert_eq!("Obsolete", catalog.gettext("Obsolete"));
+    }
+
+    #[test]
+    fn hash_table_sizes() {
+        let sizes: Vec<_> = [0, 5, 8, 9, 11, 18, 24, 77, 78].iter()
+            .map(|&count| hash_table_size(count))
+            .collect();
+        assert_eq!(vec![11, 11, 11, 13, 17, 29, 37, 103, 107], sizes);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn charset_conversion() {
+        let po = PoFile::from_file(fixtures().join("po/sk.po")).unwrap();
+        assert_eq!("Dobrý deň", po.entries[1].msgstr[0]);
+        let catalog = po.to_catalog().unwrap();
+        assert_eq!(Some("text/plain; charset=UTF-8"), catalog.header("Content-Type"));
+        assert_eq!("Dobrý deň", catalog.gettext("Hello"));
+    }
+
+    #[test]
+    fn escapes() {
+        let po = PoFile::parse(concat!(r#"msgid "\a\b\f\v\r\?\'\101\0\x41\x7e!""#, "\n",
+                                       r#"  "\n""#, "\n",
+                                       r#"msgstr """#)).unwrap();
+        assert_eq!("\u{7}\u{8}\u{c}\u{b}\r?'A\0A~!\n", po.entries[0].msgid);
+        assert_eq!(vec![""], po.entries[0].msgstr);
+        assert_eq!(Ok(PoFile::default()), PoFile::parse("# just a comment\n\n"));
+    }
+
+    #[test]
+    fn errors() {
+        let error = |text| PoFile::parse(text).unwrap_err().line();
+        assert_eq!(1, error("msgid \"a\\q\"\nmsgstr \"\""));
+        assert_eq!(1, error("msgid \"a\nmsgstr \"\""));
+        assert_eq!(1, error("msgid \"a\" x\nmsgstr \"\""));
+        assert_eq!(1, error("msgid \"a\"b\"\nmsgstr \"\""));
+        assert_eq!(1, error("msgid \"\\x123\"\nmsgstr \"\""));
+        assert_eq!(2, error("msgid \"a\"\nmsgid \"b\"\nmsgstr \"\""));
+        assert_eq!(1, error("msgid \"a\"\n"));
+        assert_eq!(1, error("\"a\"\nmsgid \"a\"\nmsgstr \"\""));
+        assert_eq!(3, error("msgid \"a\"\nmsgid_plural \"b\"\nmsgstr \"\""));
+        assert_eq!(3, error("msgid \"a\"\nmsgid_plural \"b\"\nmsgstr[1] \"\""));
+        assert_eq!(2, error("msgid \"a\"\nmsgstr[0] \"\""));
+        assert_eq!(3, error("msgid \"a\"\nmsgstr \"\"\nmsgstr \"\""));
+        assert_eq!(1, error("msgstr \"\""));
+        assert_eq!(2, error("msgid \"a\"\nmsgtext \"\""));
+        assert_eq!(3, error("msgid \"a\"\nmsgid_plural \"b\"\nmsgstr[x] \"\""));
+
+        let io_error = PoFile::from_bytes(b"msgid \"a\"").unwrap_err();
+        assert_eq!(ErrorKind::InvalidData, io_error.kind());
+        assert_eq!("missing msgstr at line 1", io_error.to_string());
+        let not_utf8 = PoFile::from_bytes(b"msgid \"\xe1\"\nmsgstr \"\"").unwrap_err();
+        assert_eq!(ErrorKind::InvalidData, not_utf8.kind());
+    }
+}
@@ -16,8 +16,15 @@
 
 extern crate libc;
 
-use std::fmt::Display;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display};
 use std::io::Result;
+use std::str::FromStr;
+
+use gettext::PluralRule;
 
 /// Trait defining how to obtain various components of a locale.
 ///
@@ -40,8 +47,20 @@ pub trait LocaleFactory {
     /// Get implementation of the Numeric locale category.
     fn get_numeric(&mut self) -> Option<Box<Numeric>> { None }
 
+    /// Get implementation of the Monetary locale category.
+    fn get_monetary(&mut self) -> Option<Box<Monetary>> { None }
+
     /// Get implementation of the Time locale category.
     fn get_time(&mut self) -> Option<Box<Time>> { None }
+
+    /// Get names of time units for formatting durations and relative times.
+    fn get_relative_time(&mut self) -> Option<Box<RelativeTime>> { None }
+
+    /// Get implementation of the Messages locale category.
+    fn get_messages(&mut self) -> Option<Box<Messages>> { None }
+
+    /// Get implementation of the Collate locale category.
+    fn get_collator(&mut self) -> Option<Box<Collator>> { None }
 }
 
 /// Auxiliary class for creating composing partial implementations of locale factories.
@@ -70,6 +89,14 @@ impl<F: LocaleFactory, S: LocaleFactory> LocaleFactory for CompositeLocaleFactor
         }
     }
 
+    fn get_monetary(&mut self) -> Option<Box<Monetary>> {
+        if let Some(v) = self.first.get_monetary() {
+            Some(v)
+        } else {
+            self.second.get_monetary()
+        }
+    }
+
     fn get_time(&mut self) -> Option<Box<Time>> {
         if let Some(v) = self.first.get_time() {
             Some(v)
@@ -77,6 +104,30 @@ impl<F: LocaleFactory, S: LocaleFactory> LocaleFactory for CompositeLocaleFactor
             self.second.get_time()
         }
     }
+
+    fn get_relative_time(&mut self) -> Option<Box<RelativeTime>> {
+        if let Some(v) = self.first.get_relative_time() {
+            Some(v)
+        } else {
+            self.second.get_relative_time()
+        }
+    }
+
+    fn get_messages(&mut self) -> Option<Box<Messages>> {
+        if let Some(v) = self.first.get_messages() {
+            Some(v)
+        } else {
+            self.second.get_messages()
+        }
+    }
+
+    fn get_collator(&mut self) -> Option<Box<Collator>> {
+        if let Some(v) = self.first.get_collator() {
+            Some(v)
+        } else {
+            self.second.get_collator()
+        }
+    }
 }
 
 /// Factory of invariant locales.
@@ -114,6 +165,15 @@ pub use linux::LibCLocaleFactory as SystemLocaleFactory;
 // have MacOS box nor cross-compiler and it does not actually contain anything system-specific yet
 pub mod macos;
 
+pub mod tz;
+
+pub mod gettext;
+
+#[cfg(feature = "uca")]
+pub mod uca;
+
+mod ere;
+
 #[cfg(target_os = "macos")]
 pub use macos::MacOSLocaleFactory as SystemLocaleFactory;
 
@@ -146,6 +206,36 @@ pub struct Numeric {
 
     /// The punctuation that separates groups of digits in long numbers.
     pub thousands_sep: String,
+
+    /// Sizes of the digit groups separated by `thousands_sep`, starting with the group nearest to
+    /// the decimal separator.
+    ///
+    /// This follows the `grouping` member of C `struct lconv`: the last size repeats for the rest
+    /// of the number, as it does when followed by 0, unless the list ends with `CHAR_MAX` (or any
+    /// value of 127 or more), in which case no further grouping is done. Empty list means no
+    /// grouping at all.
+    pub grouping: Vec<u8>,
+
+    /// The sign prepended to negative numbers.
+    ///
+    /// Standard C library does not define this for `LC_NUMERIC` and always uses `-`, but other
+    /// sources of locale data may prefer e.g. the proper minus sign (U+2212).
+    pub negative_sign: String,
+
+    /// The representation of a not-a-number floating point value.
+    pub nan: String,
+
+    /// The representation of infinite floating point value (the sign is added as for any other
+    /// number).
+    pub infinity: String,
+
+    /// Native digits, from zero to nine, used by `format_int_native` and `format_float_native`.
+    ///
+    /// `None` if the locale uses ASCII digits. Parsing always accepts these.
+    pub native_digits: Option<Vec<String>>,
+
+    /// Additional sets of digits, each from zero to nine, accepted when parsing.
+    pub input_digits: Vec<Vec<String>>,
 }
 
 impl Numeric {
@@ -162,120 +252,3590 @@ impl Numeric {
         Numeric::new(".", ",")
     }
 
+    /// Constructs numeric options with given separators, grouping digits by three.
     pub fn new(decimal_sep: &str, thousands_sep: &str) -> Numeric {
+        Numeric::new_with_grouping(decimal_sep, thousands_sep, &[3])
+    }
+
+    /// Constructs numeric options with given separators and grouping.
+    ///
+    /// See `grouping` for the meaning of the values.
+    pub fn new_with_grouping(decimal_sep: &str, thousands_sep: &str, grouping: &[u8]) -> Numeric {
         Numeric {
             decimal_sep: decimal_sep.to_string(),
             thousands_sep: thousands_sep.to_string(),
+            grouping: grouping.to_vec(),
+            negative_sign: "-".to_string(),
+            nan: "nan".to_string(),
+            infinity: "inf".to_string(),
+            native_digits: None,
+            input_digits: Vec::new(),
         }
     }
 
-    pub fn format_int<I: Display>(&self, input: I) -> String {
+    /// Formats integer with digits grouped according to `grouping`.
+    ///
+    /// Negative numbers are prefixed with `negative_sign`.
+    pub fn format_int<I: Integer>(&self, input: I) -> String {
         let s = input.to_string();
+        let digits = group_digits(s.trim_start_matches('-'), &self.grouping, &self.thousands_sep);
+        if input.is_negative() {
+            self.negative_sign.clone() + &digits
+        } else {
+            digits
+        }
+    }
+
+    /// Formats floating point number with given number of decimal places.
+    ///
+    /// The integral part is grouped according to `grouping` like in `format_int`. Negative
+    /// numbers, including negative zero, are prefixed with `negative_sign`.
+    pub fn format_float<F: Float>(&self, input: F, decimal_places: usize) -> String {
+        if input.is_nan() {
+            return self.nan.clone();
+        }
+
+        let mut buf = String::new();
+        if input.is_sign_negative() {
+            buf.push_str(&self.negative_sign);
+        }
+        if input.is_infinite() {
+            buf.push_str(&self.infinity);
+            return buf;
+        }
+
+        let s = format!("{:.*}", decimal_places, input);
+        let s = s.trim_start_matches('-');
+        let (int_part, frac_part) = match s.find('.') {
+            Some(i) => (&s[..i], Some(&s[i + 1..])),
+            None => (s, None),
+        };
+        buf.push_str(&group_digits(int_part, &self.grouping, &self.thousands_sep));
+        if let Some(frac_part) = frac_part {
+            buf.push_str(&self.decimal_sep);
+            buf.push_str(frac_part);
+        }
+        buf
+    }
+
+    /// Formats integer like `format_int`, but using `native_digits`.
+    pub fn format_int_native<I: Integer>(&self, input: I) -> String {
+        self.to_native_digits(self.format_int(input))
+    }
+
+    /// Formats floating point number like `format_float`, but using `native_digits`.
+    pub fn format_float_native<F: Float>(&self, input: F, decimal_places: usize) -> String {
+        self.to_native_digits(self.format_float(input, decimal_places))
+    }
+
+    fn to_native_digits(&self, s: String) -> String {
+        match self.native_digits {
+            Some(ref digits) => s.chars().map(|c| match c.to_digit(10) {
+                Some(d) if c.is_ascii_digit() => digits[d as usize].clone(),
+                _ => c.to_string(),
+            }).collect(),
+            None => s,
+        }
+    }
+
+    /// Parses integer written according to these options.
+    ///
+    /// Digit group separators are optional, but if there are any, they must be placed as
+    /// `grouping` prescribes. Leading and trailing whitespace is ignored. Accepts anything
+    /// `format_int` produces.
+    pub fn parse_int<I: Integer + FromStr>(&self, input: &str)
+        -> ::std::result::Result<I, ParseNumberError>
+    {
+        let (start, number) = self.parse_number(input, false)?;
+        number.parse().map_err(|_| ParseNumberError::new(ParseNumberErrorKind::OutOfRange, start))
+    }
+
+    /// Parses floating point number written according to these options.
+    ///
+    /// Same rules as in `parse_int` apply to the integral part, which may be followed by
+    /// `decimal_sep` and the fractional part. Also accepts `nan` and `infinity` (and the
+    /// conventional "nan", "inf" and "infinity" in any case). Accepts anything `format_float`
+    /// produces.
+    pub fn parse_float<F: Float + FromStr>(&self, input: &str)
+        -> ::std::result::Result<F, ParseNumberError>
+    {
+        let (start, number) = self.parse_number(input, true)?;
+        number.parse().map_err(|_| ParseNumberError::new(ParseNumberErrorKind::OutOfRange, start))
+    }
+
+    /// Converts localized number to the form understood by `FromStr`.
+    ///
+    /// Returns the position where the number starts along with the converted string.
+    fn parse_number(&self, input: &str, fraction: bool)
+        -> ::std::result::Result<(usize, String), ParseNumberError>
+    {
+        use ParseNumberErrorKind::*;
+
+        let start = input.len() - input.trim_start().len();
+        let end = start + input[start..].trim_end().len();
+        let mut pos = start;
         let mut buf = String::new();
 
-        for (i, c) in s.chars().enumerate() {
-            buf.push(c);
-            if (s.len() - i - 1) % 3 == 0 && i != s.len() - 1 {
-                buf.push_str(&self.thousands_sep[..]);
+        let rest = &input[pos..end];
+        if !self.negative_sign.is_empty() && rest.starts_with(&self.negative_sign[..]) {
+            buf.push('-');
+            pos += self.negative_sign.len();
+        } else if rest.starts_with('-') {
+            buf.push('-');
+            pos += 1;
+        } else if rest.starts_with('+') {
+            pos += 1;
+        }
+
+        if fraction {
+            let rest = &input[pos..end];
+            if rest == self.nan || rest.eq_ignore_ascii_case("nan") {
+                return Ok((start, "NaN".to_string()));
+            }
+            if rest == self.infinity || rest.eq_ignore_ascii_case("inf")
+                || rest.eq_ignore_ascii_case("infinity")
+            {
+                buf.push_str("inf");
+                return Ok((start, buf));
             }
         }
 
-        buf
+        let mut digits = 0;
+        let mut groups = Vec::new();
+        let mut separators = Vec::new();
+        let mut group = 0;
+        while pos < end {
+            let rest = &input[pos..end];
+            if let Some((digit, len)) = self.match_digit(rest) {
+                buf.push(digit);
+                digits += 1;
+                group += 1;
+                pos += len;
+            } else if let Some(len) = self.match_thousands_sep(rest) {
+                if group == 0 {
+                    return Err(ParseNumberError::new(InvalidGrouping, pos));
+                }
+                groups.push(group);
+                separators.push(pos);
+                group = 0;
+                pos += len;
+            } else {
+                break;
+            }
+        }
+        if !separators.is_empty() {
+            if group == 0 {
+                return Err(ParseNumberError::new(InvalidGrouping, separators[separators.len() - 1]));
+            }
+            groups.push(group);
+            self.check_grouping(&groups, &separators)?;
+        }
+
+        if fraction && !self.decimal_sep.is_empty() && input[pos..end].starts_with(&self.decimal_sep[..]) {
+            buf.push('.');
+            pos += self.decimal_sep.len();
+            while let Some((digit, len)) = self.match_digit(&input[pos..end]) {
+                buf.push(digit);
+                digits += 1;
+                pos += len;
+            }
+        }
+
+        if pos < end {
+            Err(ParseNumberError::new(InvalidCharacter, pos))
+        } else if digits == 0 {
+            Err(ParseNumberError::new(Empty, start))
+        } else {
+            Ok((start, buf))
+        }
+    }
+
+    /// Checks sizes of digit `groups` (from the left) against `grouping`.
+    ///
+    /// The `separators` are positions of the separators preceding each but the first group, for
+    /// error reporting.
+    fn check_grouping(&self, groups: &[usize], separators: &[usize])
+        -> ::std::result::Result<(), ParseNumberError>
+    {
+        let mut sizes = GroupSizes::new(&self.grouping);
+        for i in (1..groups.len()).rev() {
+            match sizes.next() {
+                Some(size) if size == groups[i] => (),
+                _ => return Err(ParseNumberError::new(
+                        ParseNumberErrorKind::InvalidGrouping, separators[i - 1])),
+            }
+        }
+        match sizes.next() {
+            Some(size) if groups[0] > size => Err(ParseNumberError::new(
+                    ParseNumberErrorKind::InvalidGrouping, separators[0])),
+            _ => Ok(()),
+        }
+    }
+
+    /// Recognizes a digit at the start of `input` and returns it as ASCII digit along with its
+    /// length in bytes.
+    ///
+    /// Besides ASCII digits accepts `native_digits` and `input_digits`.
+    fn match_digit(&self, input: &str) -> Option<(char, usize)> {
+        match input.chars().next() {
+            Some(c) if c.is_ascii_digit() => return Some((c, 1)),
+            None => return None,
+            _ => (),
+        }
+        for digits in self.native_digits.iter().chain(self.input_digits.iter()) {
+            for (d, digit) in digits.iter().enumerate() {
+                if !digit.is_empty() && input.starts_with(&digit[..]) {
+                    return Some(((b'0' + d as u8) as char, digit.len()));
+                }
+            }
+        }
+        None
     }
 
-    pub fn format_float<F: Display>(&self, input: F, decimal_places: usize) -> String {
-        format!("{:.*}", decimal_places, input).replace(".", &self.decimal_sep)
+    /// Recognizes digit group separator at the start of `input` and returns its length in bytes.
+    ///
+    /// Plain space is accepted in place of any other whitespace separator (like no-break space)
+    /// as that is what users usually type.
+    fn match_thousands_sep(&self, input: &str) -> Option<usize> {
+        if self.thousands_sep.is_empty() {
+            None
+        } else if input.starts_with(&self.thousands_sep[..]) {
+            Some(self.thousands_sep.len())
+        } else if input.starts_with(' ') && self.thousands_sep.chars().all(char::is_whitespace) {
+            Some(1)
+        } else {
+            None
+        }
     }
 }
 
-// ---- time stuff ---
+/// Primitive integer types that can be formatted by `Numeric`.
+pub trait Integer: Copy + Display {
+    /// Returns whether the value is less than zero.
+    fn is_negative(self) -> bool;
+}
 
-#[derive(Debug, Clone)]
-pub struct Time {
-    month_names: Vec<String>,
-    long_month_names: Vec<String>,
-    day_names: Vec<String>,
-    long_day_names: Vec<String>,
+macro_rules! impl_integer_signed {
+    ($($t:ty)*) => ($(
+        impl Integer for $t {
+            fn is_negative(self) -> bool { self < 0 }
+        }
+    )*)
 }
 
-impl Time {
-    pub fn load_user_locale() -> Result<Time> {
-        if let Ok(mut factory) = SystemLocaleFactory::new("") {
-            if let Some(time) = factory.get_time() {
-                return Ok(*time);
-            }
+macro_rules! impl_integer_unsigned {
+    ($($t:ty)*) => ($(
+        impl Integer for $t {
+            fn is_negative(self) -> bool { false }
         }
-        Ok(Time::english())
+    )*)
+}
+
+impl_integer_signed!(i8 i16 i32 i64 i128 isize);
+impl_integer_unsigned!(u8 u16 u32 u64 u128 usize);
+
+/// Primitive floating point types that can be formatted by `Numeric`.
+pub trait Float: Copy + Display {
+    /// Returns whether the value is not-a-number.
+    fn is_nan(self) -> bool;
+
+    /// Returns whether the value is positive or negative infinity.
+    fn is_infinite(self) -> bool;
+
+    /// Returns whether the value has negative sign, including negative zero.
+    fn is_sign_negative(self) -> bool;
+}
+
+macro_rules! impl_float {
+    ($($t:ident)*) => ($(
+        impl Float for $t {
+            fn is_nan(self) -> bool { $t::is_nan(self) }
+            fn is_infinite(self) -> bool { $t::is_infinite(self) }
+            fn is_sign_negative(self) -> bool { $t::is_sign_negative(self) }
+        }
+    )*)
+}
+
+impl_float!(f32 f64);
+
+/// Insert `sep` between groups of `digits` as prescribed by `grouping`.
+///
+/// The `digits` must only contain single-byte characters; the grouping is counted from the end.
+fn group_digits(digits: &str, grouping: &[u8], sep: &str) -> String {
+    if sep.is_empty() {
+        return digits.to_string();
     }
 
-    pub fn english() -> Time {
-        Time {
-            month_names: vec![
-                "Jan".to_string(),  "Feb".to_string(),  "Mar".to_string(),
-                "Apr".to_string(),  "May".to_string(),  "Jun".to_string(),
-                "Jul".to_string(),  "Aug".to_string(),  "Sep".to_string(),
-                "Oct".to_string(),  "Nov".to_string(),  "Dec".to_string(),
-            ],
-            long_month_names: vec![
-                "January".to_string(),    "February".to_string(),
-                "March".to_string(),      "April".to_string(),
-                "May".to_string(),        "June".to_string(),
-                "July".to_string(),       "August".to_string(),
-                "September".to_string(),  "October".to_string(),
-                "November".to_string(),   "December".to_string(),
-            ],
-            day_names: vec![
-                "Sun".to_string(),
-                "Mon".to_string(),  "Tue".to_string(),  "Wed".to_string(),
-                "Thu".to_string(),  "Fri".to_string(),  "Sat".to_string(),
-            ],
-            long_day_names: vec![
-                "Sunday".to_string(),
-                "Monday".to_string(),    "Tuesday".to_string(),  "Wednesday".to_string(),
-                "Thursday".to_string(),  "Friday".to_string(),   "Saturday".to_string(),
-            ],
+    let mut groups = Vec::new();
+    let mut end = digits.len();
+    for size in GroupSizes::new(grouping) {
+        if end <= size {
+            break;
         }
+        groups.push(&digits[end - size..end]);
+        end -= size;
     }
+    groups.push(&digits[..end]);
+    groups.reverse();
+    groups.join(sep)
+}
 
-    pub fn long_month_name(&self, months_from_january: usize) -> String {
-        self.long_month_names[months_from_january].clone()
+/// Iterator over digit group sizes described by `Numeric::grouping`, from the decimal separator
+/// out. Ends when no further grouping is to be done.
+struct GroupSizes<'a> {
+    sizes: ::std::slice::Iter<'a, u8>,
+    last: usize,
+}
+
+impl<'a> GroupSizes<'a> {
+    fn new(grouping: &'a [u8]) -> Self {
+        GroupSizes { sizes: grouping.iter(), last: 0 }
     }
+}
+
+impl<'a> Iterator for GroupSizes<'a> {
+    type Item = usize;
 
-    pub fn short_month_name(&self, months_from_january: usize) -> String {
-        self.month_names[months_from_january].clone()
+    fn next(&mut self) -> Option<usize> {
+        match self.sizes.next() {
+            // 0 ends the list like in a C string, so the last size repeats
+            Some(&0) => self.sizes = [].iter(),
+            Some(&g) if g >= 127 => {
+                self.sizes = [].iter();
+                self.last = 0;
+            }
+            Some(&g) => self.last = g as usize,
+            None => (), // repeat the last size
+        }
+        if self.last == 0 { None } else { Some(self.last) }
     }
+}
+
+/// Kind of error encountered when parsing number with `Numeric`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseNumberErrorKind {
+    /// The input does not contain any digits.
+    Empty,
+    /// The input contains a character that is not valid at that position.
+    InvalidCharacter,
+    /// Digit group separator is misplaced according to the grouping rules.
+    InvalidGrouping,
+    /// The number does not fit in the target type.
+    OutOfRange,
+    /// The fractional part has more digits than the currency allows.
+    FractionTooLong,
+}
+
+/// Error returned from `Numeric::parse_int`, `Numeric::parse_float` and `Monetary::parse`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseNumberError {
+    kind: ParseNumberErrorKind,
+    position: usize,
+}
 
-    pub fn long_day_name(&self, days_from_sunday: usize) -> String {
-        self.day_names[days_from_sunday].clone()
+impl ParseNumberError {
+    fn new(kind: ParseNumberErrorKind, position: usize) -> Self {
+        ParseNumberError { kind, position }
     }
 
-    pub fn short_day_name(&self, days_from_sunday: usize) -> String {
-        self.day_names[days_from_sunday].clone()
+    /// Returns what went wrong.
+    pub fn kind(&self) -> ParseNumberErrorKind {
+        self.kind
     }
 
+    /// Returns byte offset in the input at which the problem was detected.
+    pub fn position(&self) -> usize {
+        self.position
+    }
 }
 
-// ---- tests ----
+impl Display for ParseNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let what = match self.kind {
+            ParseNumberErrorKind::Empty => "no digits in number",
+            ParseNumberErrorKind::InvalidCharacter => "invalid character in number",
+            ParseNumberErrorKind::InvalidGrouping => "misplaced digit group separator",
+            ParseNumberErrorKind::OutOfRange => "number out of range",
+            ParseNumberErrorKind::FractionTooLong => "too many fractional digits",
+        };
+        write!(f, "{} at position {}", what, self.position)
+    }
+}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+impl Error for ParseNumberError {}
 
-    #[test]
-    fn thousands_separator() {
-        let numeric_options = Numeric::new("/", "=");
-        assert_eq!("1=234=567".to_string(), numeric_options.format_int(1234567))
+// ---- monetary stuff ----
+
+/// Position of the sign in formatted monetary amount.
+///
+/// Corresponds to the `p_sign_posn` and `n_sign_posn` members of C `struct lconv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignPosition {
+    /// The amount and currency symbol are surrounded by parentheses instead of a sign.
+    Parentheses,
+    /// The sign precedes the amount and currency symbol.
+    Before,
+    /// The sign follows the amount and currency symbol.
+    After,
+    /// The sign immediately precedes the currency symbol.
+    BeforeSymbol,
+    /// The sign immediately follows the currency symbol.
+    AfterSymbol,
+}
+
+/// Placement of space between currency symbol, sign and amount.
+///
+/// Corresponds to the `p_sep_by_space` and `n_sep_by_space` members of C `struct lconv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolSpacing {
+    /// No space separates the currency symbol and amount.
+    NoSpace,
+    /// Space separates the currency symbol from the amount. If the sign is adjacent to the
+    /// currency symbol, the space separates both from the amount.
+    SymbolSpace,
+    /// If the currency symbol and sign are adjacent, space separates them. Otherwise space
+    /// separates the sign from the amount.
+    SignSpace,
+}
+
+/// Layout of monetary amounts of one sign.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignFormat {
+    /// Whether the currency symbol precedes the amount.
+    pub cs_precedes: bool,
+
+    /// Where to put space between currency symbol, sign and amount.
+    pub sep_by_space: SymbolSpacing,
+
+    /// Where to put the sign.
+    pub sign_posn: SignPosition,
+}
+
+impl SignFormat {
+    /// Constructs the layout from values of the corresponding C `struct lconv` members.
+    ///
+    /// Unspecified (`CHAR_MAX` or negative) and invalid values are replaced with the same
+    /// defaults GNU libc `strfmon` uses: symbol first, no space and sign before everything.
+    pub fn from_lconv(cs_precedes: i8, sep_by_space: i8, sign_posn: i8) -> SignFormat {
+        SignFormat {
+            cs_precedes: cs_precedes != 0,
+            sep_by_space: match sep_by_space {
+                1 => SymbolSpacing::SymbolSpace,
+                2 => SymbolSpacing::SignSpace,
+                _ => SymbolSpacing::NoSpace,
+            },
+            sign_posn: match sign_posn {
+                0 => SignPosition::Parentheses,
+                2 => SignPosition::After,
+                3 => SignPosition::BeforeSymbol,
+                4 => SignPosition::AfterSymbol,
+                _ => SignPosition::Before,
+            },
+        }
     }
+}
 
-    #[test]
-    fn thousands_separator_2() {
-        let numeric_options = Numeric::new("/", "=");
-        assert_eq!("123=456".to_string(), numeric_options.format_int(123456))
+/// Information on how to format monetary amounts in either local or international form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrencyFormat {
+    /// The currency symbol.
+    ///
+    /// For international form this is the ISO 4217 code, which C library data follow with the
+    /// separator character (usually space).
+    pub symbol: String,
+
+    /// Number of digits after the decimal separator.
+    pub frac_digits: u8,
+
+    /// Layout of positive amounts.
+    pub positive: SignFormat,
+
+    /// Layout of negative amounts.
+    pub negative: SignFormat,
+}
+
+/// Which currency symbol was used in a monetary amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurrencySymbol {
+    /// The local currency symbol, `Monetary::local.symbol`.
+    Local,
+    /// The international currency symbol, `Monetary::international.symbol`.
+    International,
+}
+
+/// A currency together with the period when it is in use.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Currency {
+    /// Format using local currency symbol.
+    pub local: CurrencyFormat,
+
+    /// Format using international currency symbol.
+    pub international: CurrencyFormat,
+
+    /// The first day the currency is valid.
+    pub valid_from: Date,
+
+    /// The last day the currency is valid.
+    pub valid_to: Date,
+}
+
+impl Currency {
+    /// Returns whether the currency is valid on given day.
+    pub fn is_valid_at(&self, date: Date) -> bool {
+        self.valid_from <= date && date <= self.valid_to
     }
+}
 
-    #[test]
-    fn thousands_separator_3() {
-        let numeric_options = Numeric::new("/", "=");
-        assert_eq!("12=345=678".to_string(), numeric_options.format_int(12345678))
+/// Information on how to format monetary amounts.
+#[derive(Debug, Clone)]
+pub struct Monetary {
+    /// The punctuation that separates the fractional part of the amount.
+    pub decimal_sep: String,
+
+    /// The punctuation that separates groups of digits in large amounts.
+    pub thousands_sep: String,
+
+    /// Sizes of the digit groups separated by `thousands_sep`. Same as `Numeric::grouping`.
+    pub grouping: Vec<u8>,
+
+    /// The sign of non-negative amounts. Usually empty.
+    pub positive_sign: String,
+
+    /// The sign of negative amounts.
+    pub negative_sign: String,
+
+    /// Format using local currency symbol.
+    pub local: CurrencyFormat,
+
+    /// Format using international currency symbol.
+    pub international: CurrencyFormat,
+
+    /// The first day the `local` and `international` currency is valid.
+    pub valid_from: Date,
+
+    /// The last day the `local` and `international` currency is valid.
+    pub valid_to: Date,
+
+    /// The second currency, for locales that use two, e.g. during transition to euro.
+    pub dual: Option<Currency>,
+}
+
+impl Monetary {
+    pub fn load_user_locale() -> Result<Monetary> {
+        if let Ok(mut factory) = SystemLocaleFactory::new("") {
+            if let Some(monetary) = factory.get_monetary() {
+                return Ok(*monetary);
+            }
+        }
+        Ok(Monetary::english())
+    }
+
+    /// Returns conventions for amounts in US dollars.
+    pub fn english() -> Monetary {
+        let sign_format = |sep_by_space| SignFormat {
+            cs_precedes: true,
+            sep_by_space,
+            sign_posn: SignPosition::Before,
+        };
+        Monetary {
+            decimal_sep: ".".to_string(),
+            thousands_sep: ",".to_string(),
+            grouping: vec![3],
+            positive_sign: "".to_string(),
+            negative_sign: "-".to_string(),
+            local: CurrencyFormat {
+                symbol: "$".to_string(),
+                frac_digits: 2,
+                positive: sign_format(SymbolSpacing::NoSpace),
+                negative: sign_format(SymbolSpacing::NoSpace),
+            },
+            international: CurrencyFormat {
+                symbol: "USD ".to_string(),
+                frac_digits: 2,
+                positive: sign_format(SymbolSpacing::SymbolSpace),
+                negative: sign_format(SymbolSpacing::SymbolSpace),
+            },
+            valid_from: Date::new(1, 1, 1),
+            valid_to: Date::new(9999, 12, 31),
+            dual: None,
+        }
+    }
+
+    /// Returns the primary currency with its validity period.
+    pub fn currency(&self) -> Currency {
+        Currency {
+            local: self.local.clone(),
+            international: self.international.clone(),
+            valid_from: self.valid_from,
+            valid_to: self.valid_to,
+        }
+    }
+
+    /// Returns the currency valid on given day.
+    ///
+    /// The primary currency is preferred when both are valid. Returns `None` if neither is.
+    pub fn currency_at(&self, date: Date) -> Option<Currency> {
+        let primary = self.currency();
+        if primary.is_valid_at(date) {
+            return Some(primary);
+        }
+        self.dual.iter().find(|c| c.is_valid_at(date)).cloned()
+    }
+
+    /// Returns conventions for amounts in the currency valid on given day.
+    ///
+    /// If the dual currency is the one valid, the result has it swapped with the primary one,
+    /// so `format`, `parse` and other methods use it. Returns `None` if neither currency is
+    /// valid on that day.
+    pub fn at_date(&self, date: Date) -> Option<Monetary> {
+        if self.currency().is_valid_at(date) {
+            return Some(self.clone());
+        }
+        match self.dual {
+            Some(ref dual) if dual.is_valid_at(date) => Some(Monetary {
+                local: dual.local.clone(),
+                international: dual.international.clone(),
+                valid_from: dual.valid_from,
+                valid_to: dual.valid_to,
+                dual: Some(self.currency()),
+                ..self.clone()
+            }),
+            _ => None,
+        }
+    }
+
+    /// Formats amount in local currency.
+    ///
+    /// The `amount` is given in minor units, that is multiplied by 10 to the power of
+    /// `local.frac_digits` (e.g. in cents), so no rounding ever takes place. The result is the
+    /// same as from C `strfmon` with `%n` format.
+    pub fn format(&self, amount: i64) -> String {
+        self.format_currency(&self.local, false, amount)
+    }
+
+    /// Formats amount in international currency.
+    ///
+    /// Like `format`, but uses `international` format like C `strfmon` with `%i` format. The
+    /// fourth character of the symbol, if any, is taken as the separator to use in place of
+    /// space between the symbol and amount, as POSIX prescribes for `int_curr_symbol`.
+    pub fn format_international(&self, amount: i64) -> String {
+        self.format_currency(&self.international, true, amount)
+    }
+
+    fn format_currency(&self, currency: &CurrencyFormat, international: bool, amount: i64)
+        -> String
+    {
+        let negative = amount < 0;
+        let (format, sign) = if negative {
+            let sign = if self.negative_sign.is_empty() { "-" } else { &self.negative_sign[..] };
+            (&currency.negative, sign)
+        } else {
+            (&currency.positive, &self.positive_sign[..])
+        };
+        let (symbol, space) = match currency.symbol.char_indices().nth(3) {
+            Some((i, c)) if international => (&currency.symbol[..i], c),
+            _ => (&currency.symbol[..], ' '),
+        };
+
+        // The logic follows GNU libc strfmon, including the choice of which spaces are replaced
+        // by the international separator.
+        let cs_precedes = format.cs_precedes;
+        let sign_posn = format.sign_posn;
+        let mut sep_by_space = format.sep_by_space;
+        if sep_by_space == SymbolSpacing::SignSpace && (sign_posn == SignPosition::Parentheses
+            || (sign_posn == SignPosition::Before && !cs_precedes)
+            || (sign_posn == SignPosition::After && cs_precedes))
+        {
+            // sign and symbol are not adjacent, so no separator
+            sep_by_space = SymbolSpacing::NoSpace;
+        }
+
+        let mut buf = String::new();
+        if sign_posn == SignPosition::Parentheses && negative {
+            buf.push('(');
+        }
+
+        if cs_precedes {
+            if sign_posn == SignPosition::Before || sign_posn == SignPosition::BeforeSymbol {
+                buf.push_str(sign);
+                if sep_by_space == SymbolSpacing::SignSpace {
+                    buf.push(' ');
+                }
+            }
+            buf.push_str(symbol);
+            if sign_posn == SignPosition::AfterSymbol {
+                if sep_by_space == SymbolSpacing::SignSpace {
+                    buf.push(space);
+                }
+                buf.push_str(sign);
+                if sep_by_space == SymbolSpacing::SymbolSpace {
+                    buf.push(' ');
+                }
+            } else if sep_by_space == SymbolSpacing::SymbolSpace {
+                buf.push(space);
+            }
+        } else if sign_posn == SignPosition::Before {
+            buf.push_str(sign);
+        }
+
+        buf.push_str(&self.format_amount(amount.unsigned_abs(), currency.frac_digits));
+
+        if !cs_precedes {
+            if sign_posn == SignPosition::BeforeSymbol {
+                if sep_by_space == SymbolSpacing::SymbolSpace {
+                    buf.push(' ');
+                }
+                buf.push_str(sign);
+                if sep_by_space == SymbolSpacing::SignSpace {
+                    buf.push(space);
+                }
+            } else if sep_by_space == SymbolSpacing::SymbolSpace {
+                buf.push(space);
+            }
+            buf.push_str(symbol);
+            if sign_posn == SignPosition::AfterSymbol {
+                if sep_by_space == SymbolSpacing::SignSpace {
+                    buf.push(' ');
+                }
+                buf.push_str(sign);
+            }
+        }
+
+        if sign_posn == SignPosition::After {
+            if sep_by_space == SymbolSpacing::SignSpace {
+                buf.push(' ');
+            }
+            buf.push_str(sign);
+        }
+        if sign_posn == SignPosition::Parentheses && negative {
+            buf.push(')');
+        }
+        buf
+    }
+
+    /// Parses amount written in local or international currency.
+    ///
+    /// Accepts the amount with either currency symbol or none at all, with the sign, the
+    /// currency symbol and parentheses for negative amounts in any sensible position. The digit
+    /// groups are checked as in `Numeric::parse_int`.
+    ///
+    /// Returns the amount in minor units, as `format` takes it, along with the currency symbol
+    /// that was found. If it was international, `international.frac_digits` is used to determine
+    /// the minor units, otherwise `local.frac_digits`.
+    pub fn parse(&self, input: &str)
+        -> ::std::result::Result<(i64, Option<CurrencySymbol>), ParseNumberError>
+    {
+        use ParseNumberErrorKind::*;
+
+        let mut start = input.len() - input.trim_start().len();
+        let mut end = start + input[start..].trim_end().len();
+        let mut negative = false;
+        let mut sign = false;
+        let mut symbol = None;
+
+        if input[start..end].starts_with('(') {
+            if !input[start..end].ends_with(')') || end - start < 2 {
+                return Err(ParseNumberError::new(InvalidCharacter, end));
+            }
+            negative = true;
+            sign = true;
+            start += 1;
+            end -= 1;
+        }
+
+        // Skip symbol, sign and spaces on both sides of the amount.
+        for &suffix in &[false, true] {
+            loop {
+                let rest = &input[start..end];
+                let token = if suffix { rest.chars().next_back() } else { rest.chars().next() };
+                let len = if let Some(c) = token.filter(|c| c.is_whitespace()) {
+                    c.len_utf8()
+                } else if let (false, Some((len, neg))) = (sign, self.match_sign(rest, suffix)) {
+                    negative = neg;
+                    sign = true;
+                    len
+                } else if let (None, Some((len, sym))) = (symbol, self.match_symbol(rest, suffix)) {
+                    symbol = Some(sym);
+                    len
+                } else {
+                    break;
+                };
+                if suffix { end -= len } else { start += len }
+            }
+        }
+
+        let decimal_sep = if self.decimal_sep.is_empty() { "." } else { &self.decimal_sep[..] };
+        let numeric = Numeric::new_with_grouping(decimal_sep, &self.thousands_sep, &self.grouping);
+        let number = match numeric.parse_number(&input[start..end], true) {
+            Ok((_, number)) => number,
+            Err(e) => return Err(ParseNumberError::new(e.kind, start + e.position)),
+        };
+        if !number.bytes().all(|b| b.is_ascii_digit() || b == b'.' || (b == b'-' && !sign)) {
+            return Err(ParseNumberError::new(InvalidCharacter, start));
+        }
+        let negative = negative || number.starts_with('-');
+        let number = number.trim_start_matches('-');
+
+        let frac_digits = match symbol {
+            Some(CurrencySymbol::International) => self.international.frac_digits,
+            _ => self.local.frac_digits,
+        } as usize;
+        let (int_part, frac_part) = match number.find('.') {
+            Some(i) => (&number[..i], &number[i + 1..]),
+            None => (number, ""),
+        };
+        if frac_part.len() > frac_digits {
+            let position = input[start..end].find(decimal_sep).unwrap_or(0)
+                + decimal_sep.len() + frac_digits;
+            return Err(ParseNumberError::new(FractionTooLong, start + position));
+        }
+        let digits = format!("{}{}{}{:0<4$}", if negative { "-" } else { "" }, int_part, frac_part,
+                             "", frac_digits - frac_part.len());
+        match digits.parse() {
+            Ok(amount) => Ok((amount, symbol)),
+            Err(_) => Err(ParseNumberError::new(OutOfRange, start)),
+        }
+    }
+
+    /// Recognizes sign at the start or end of `input`. Returns its length and whether it is
+    /// negative.
+    fn match_sign(&self, input: &str, at_end: bool) -> Option<(usize, bool)> {
+        let signs = [(&self.negative_sign[..], true), ("-", true),
+                     (&self.positive_sign[..], false), ("+", false)];
+        signs.iter()
+            .filter(|&&(s, _)| !s.is_empty())
+            .find(|&&(s, _)| if at_end { input.ends_with(s) } else { input.starts_with(s) })
+            .map(|&(s, negative)| (s.len(), negative))
+    }
+
+    /// Recognizes currency symbol at the start or end of `input`. Returns its length and which
+    /// symbol it is.
+    ///
+    /// The international symbol is matched without the separator and ignoring case.
+    fn match_symbol(&self, input: &str, at_end: bool) -> Option<(usize, CurrencySymbol)> {
+        let local = &self.local.symbol;
+        let found = if at_end {
+            input.ends_with(&local[..])
+        } else {
+            input.starts_with(&local[..])
+        };
+        if !local.is_empty() && found {
+            return Some((local.len(), CurrencySymbol::Local));
+        }
+        let int = match self.international.symbol.char_indices().nth(3) {
+            Some((i, _)) => &self.international.symbol[..i],
+            None => &self.international.symbol[..],
+        };
+        if int.is_empty() || input.len() < int.len() {
+            return None;
+        }
+        let candidate = if at_end {
+            input.get(input.len() - int.len()..)
+        } else {
+            input.get(..int.len())
+        };
+        match candidate {
+            Some(c) if c.eq_ignore_ascii_case(int) => {
+                Some((int.len(), CurrencySymbol::International))
+            }
+            _ => None,
+        }
+    }
+
+    /// Formats the number part of the amount given in minor units.
+    fn format_amount(&self, amount: u64, frac_digits: u8) -> String {
+        let frac_digits = frac_digits as usize;
+        let digits = format!("{:01$}", amount, frac_digits + 1);
+        let (int_part, frac_part) = digits.split_at(digits.len() - frac_digits);
+        let mut buf = group_digits(int_part, &self.grouping, &self.thousands_sep);
+        if frac_digits > 0 {
+            // C library falls back to decimal point when the locale does not define it.
+            buf.push_str(if self.decimal_sep.is_empty() { "." } else { &self.decimal_sep });
+            buf.push_str(frac_part);
+        }
+        buf
+    }
+}
+
+// ---- time stuff ---
+
+/// A calendar date in the proleptic Gregorian calendar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    /// The year.
+    pub year: i32,
+
+    /// The month, from 1 for January to 12 for December.
+    pub month: u8,
+
+    /// The day of the month, from 1.
+    pub day: u8,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u8, day: u8) -> Date {
+        Date { year, month, day }
+    }
+
+    /// Constructs the date from a number written as `YYYYMMDD`, the form C library uses in
+    /// locale data.
+    pub fn from_yyyymmdd(value: u32) -> Date {
+        Date::new((value / 10000) as i32, (value / 100 % 100) as u8, (value % 100) as u8)
+    }
+
+    /// Constructs the date given number of days since 1970-01-01.
+    ///
+    /// The days must be within the range of years that fit in `i32`, otherwise the year wraps.
+    pub fn from_days_since_epoch(days: i64) -> Date {
+        // Days in 400-year cycles starting with March 1st, 0000
+        let days = days + 719468;
+        let era = days.div_euclid(146097);
+        let day_of_era = days - era * 146097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524
+                           - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * month + 2) / 5 + 1) as u8;
+        let month = if month < 10 { month + 3 } else { month - 9 } as u8;
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        Date::new(year as i32, month, day)
+    }
+
+    /// Returns number of days since 1970-01-01, negative for earlier dates.
+    pub fn days_since_epoch(&self) -> i64 {
+        let (month, day) = (self.month as i64, self.day as i64);
+        let year = self.year as i64 - if month <= 2 { 1 } else { 0 };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+
+    /// Returns the day of the week, as number of days from Sunday.
+    pub fn day_of_week(&self) -> u8 {
+        // 1970-01-01 was Thursday
+        (self.days_since_epoch() + 4).rem_euclid(7) as u8
+    }
+
+    /// Returns the day of the week.
+    pub fn weekday(&self) -> Weekday {
+        Weekday::from_days_from_sunday(self.day_of_week() as i64)
+    }
+
+    /// Returns the day of the year, from 1 for January 1st.
+    pub fn day_of_year(&self) -> u16 {
+        let before = if self.month > 2 && is_leap_year(self.year) { 1 } else { 0 };
+        DAYS_BEFORE_MONTH[(self.month as usize).wrapping_sub(1) % 12] + before + self.day as u16
+    }
+
+    /// Returns the ISO 8601 week-based year and week number.
+    ///
+    /// Weeks start on Monday and the first week of a year is the one containing its first
+    /// Thursday, so a few days at the start or end of a calendar year may belong to the
+    /// neighbouring week-based year.
+    pub fn iso_week(&self) -> (i32, u8) {
+        // Returns number of days since the first day of first week of the year
+        fn iso_week_days(day_of_year: i32, day_of_week: i32) -> i32 {
+            // (day_of_week - day_of_year) must not go negative for the modulo
+            let big_enough_multiple_of_7 = (366 / 7 + 2) * 7;
+            day_of_year - (day_of_year - day_of_week + 4 + big_enough_multiple_of_7) % 7 + 3
+        }
+
+        let day_of_year = self.day_of_year() as i32 - 1;
+        let day_of_week = self.day_of_week() as i32;
+        let mut year = self.year;
+        let mut days = iso_week_days(day_of_year, day_of_week);
+        if days < 0 {
+            year -= 1;
+            days = iso_week_days(day_of_year + days_in_year(year), day_of_week);
+        } else {
+            let next = iso_week_days(day_of_year - days_in_year(year), day_of_week);
+            if next >= 0 {
+                year += 1;
+                days = next;
+            }
+        }
+        (year, (days / 7 + 1) as u8)
+    }
+}
+
+/// Seconds since 1970-01-01 00:00:00 of the first and last second of the years `Date` can hold.
+const MIN_TIMESTAMP: i64 = -67768100567971200;
+const MAX_TIMESTAMP: i64 = 67767976233532799;
+
+const DAYS_BEFORE_MONTH: [u16; 12] = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+
+fn is_leap_year(year: i32) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_year(year: i32) -> i32 {
+    if is_leap_year(year) { 366 } else { 365 }
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Month of the year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Month {
+    January = 1,
+    February,
+    March,
+    April,
+    May,
+    June,
+    July,
+    August,
+    September,
+    October,
+    November,
+    December,
+}
+
+impl Month {
+    /// Returns the month with given number, from 1 for January to 12 for December.
+    pub fn from_number(number: u8) -> Option<Month> {
+        use Month::*;
+        let months = [January, February, March, April, May, June, July, August, September,
+                      October, November, December];
+        months.get((number as usize).wrapping_sub(1)).cloned()
+    }
+
+    /// Returns number of the month, from 1 for January to 12 for December.
+    pub fn number(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Day of the week.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Weekday {
+    Sunday,
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+}
+
+impl Weekday {
+    /// Returns the day given number of days after Sunday, wrapping around the week.
+    pub fn from_days_from_sunday(days: i64) -> Weekday {
+        use Weekday::*;
+        let days_of_week = [Sunday, Monday, Tuesday, Wednesday, Thursday, Friday, Saturday];
+        days_of_week[days.rem_euclid(7) as usize]
+    }
+
+    /// Returns number of days from Sunday, from 0 for Sunday to 6 for Saturday.
+    pub fn days_from_sunday(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns the day given number of days later, or earlier if negative.
+    pub fn add_days(self, days: i64) -> Weekday {
+        Weekday::from_days_from_sunday(self as i64 + days.rem_euclid(7))
+    }
+}
+
+/// Date and time of day, the C `struct tm` counterpart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateTime {
+    /// The calendar date.
+    pub date: Date,
+
+    /// Hours since midnight, from 0 to 23.
+    pub hour: u8,
+
+    /// Minutes after the hour, from 0 to 59.
+    pub minute: u8,
+
+    /// Seconds after the minute, from 0 to 60 (for leap second).
+    pub second: u8,
+
+    /// Offset from UTC in seconds, positive east of Greenwich, if the time zone is known.
+    pub utc_offset: Option<i32>,
+
+    /// Abbreviated name of the time zone, like `CET`. May be empty.
+    pub zone: String,
+}
+
+impl DateTime {
+    /// Constructs the time without time zone information.
+    pub fn new(date: Date, hour: u8, minute: u8, second: u8) -> DateTime {
+        DateTime { date, hour, minute, second, utc_offset: None, zone: String::new() }
+    }
+
+    /// Constructs the time given number of seconds since 1970-01-01 00:00:00 UTC, in time zone
+    /// `utc_offset` seconds east of Greenwich. The `zone` is left empty.
+    ///
+    /// Times beyond the years `Date` can hold are clamped to its first or last second.
+    pub fn from_timestamp(timestamp: i64, utc_offset: i32) -> DateTime {
+        let local = timestamp.saturating_add(utc_offset as i64).clamp(MIN_TIMESTAMP, MAX_TIMESTAMP);
+        let seconds = local.rem_euclid(86400);
+        DateTime {
+            date: Date::from_days_since_epoch(local.div_euclid(86400)),
+            hour: (seconds / 3600) as u8,
+            minute: (seconds / 60 % 60) as u8,
+            second: (seconds % 60) as u8,
+            utc_offset: Some(utc_offset),
+            zone: String::new(),
+        }
+    }
+
+    /// Returns number of seconds since 1970-01-01 00:00:00 UTC.
+    ///
+    /// Time without known `utc_offset` is taken to be in UTC.
+    pub fn timestamp(&self) -> i64 {
+        self.date.days_since_epoch() * 86400 + self.hour as i64 * 3600 + self.minute as i64 * 60
+            + self.second as i64 - self.utc_offset.unwrap_or(0) as i64
+    }
+}
+
+/// Direction in which years of an era are numbered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EraDirection {
+    /// Years closer to the start date have lower numbers (`+`).
+    Ascending,
+
+    /// Years closer to the start date have higher numbers (`-`).
+    Descending,
+}
+
+/// Where an era ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EraEnd {
+    /// The era ends with given day.
+    Date(Date),
+
+    /// The era extends back indefinitely (`-*`).
+    BeginningOfTime,
+
+    /// The era extends forward indefinitely (`+*`).
+    EndOfTime,
+}
+
+/// Period of years counted from its own start, like reign of an emperor, as described by the
+/// `era` keyword of POSIX `LC_TIME`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Era {
+    /// How the years are numbered.
+    pub direction: EraDirection,
+
+    /// Number of the year containing `start`.
+    pub offset: i32,
+
+    /// The first day of the era.
+    pub start: Date,
+
+    /// The last day of the era.
+    pub end: EraEnd,
+
+    /// The name of the era, used for `%EC`.
+    pub name: String,
+
+    /// Pattern of the year in the era, used for `%EY`. Usually made of `%EC` and `%Ey`.
+    pub format: String,
+}
+
+impl Era {
+    /// Parses era definition in the `direction:offset:start_date:end_date:era_name:era_format`
+    /// form used by POSIX locales, like `+:2:2020/01/01:+*:令和:%EC%Ey年`.
+    ///
+    /// Dates are written as `yyyy/mm/dd` where negative years count from 1 BC, so they are
+    /// one off from years of `Date`, which has year 0 for 1 BC. Returns `None` if the entry is
+    /// malformed.
+    pub fn parse(entry: &str) -> Option<Era> {
+        let mut parts = entry.splitn(6, ':');
+        let direction = match parts.next()? {
+            "+" => EraDirection::Ascending,
+            "-" => EraDirection::Descending,
+            _ => return None,
+        };
+        let offset = parts.next()?.parse().ok()?;
+        let start = parse_era_date(parts.next()?)?;
+        let end = match parts.next()? {
+            "-*" => EraEnd::BeginningOfTime,
+            "+*" => EraEnd::EndOfTime,
+            date => EraEnd::Date(parse_era_date(date)?),
+        };
+        let name = parts.next()?.to_string();
+        let format = parts.next()?.to_string();
+        // the format must not refer to itself
+        if format.contains("EY") {
+            return None;
+        }
+        Some(Era { direction, offset, start, end, name, format })
+    }
+
+    /// Returns whether the day falls within the era.
+    pub fn contains(&self, date: Date) -> bool {
+        match self.end {
+            EraEnd::Date(end) if end < self.start => end <= date && date <= self.start,
+            EraEnd::Date(end) => self.start <= date && date <= end,
+            EraEnd::BeginningOfTime => date <= self.start,
+            EraEnd::EndOfTime => self.start <= date,
+        }
+    }
+
+    /// Returns number of the year of given day in the era, as used for `%Ey`.
+    pub fn year_of(&self, date: Date) -> i32 {
+        self.offset + (date.year - self.start.year) * self.year_step()
+    }
+
+    /// Returns the proleptic Gregorian year of the year with given number in the era.
+    pub fn gregorian_year(&self, era_year: i32) -> i32 {
+        self.start.year + (era_year - self.offset) * self.year_step()
+    }
+
+    /// Returns how the year number changes from one Gregorian year to the next.
+    fn year_step(&self) -> i32 {
+        let forward = match self.end {
+            EraEnd::Date(end) => self.start <= end,
+            EraEnd::BeginningOfTime => false,
+            EraEnd::EndOfTime => true,
+        };
+        if forward == (self.direction == EraDirection::Ascending) { 1 } else { -1 }
+    }
+}
+
+/// Parses `yyyy/mm/dd` date of era definition.
+fn parse_era_date(text: &str) -> Option<Date> {
+    let mut parts = text.splitn(3, '/');
+    let year: i32 = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    // there is no year 0 between 1 BC and 1 AD
+    Some(Date::new(if year < 0 { year + 1 } else { year }, month, day))
+}
+
+/// Locale conventions for weeks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeekInfo {
+    /// The day weeks start with in calendars and week numbers.
+    pub first_weekday: Weekday,
+
+    /// Minimal number of days of a year its first week must have, 4 for ISO 8601 weeks.
+    pub min_days_in_first_week: u8,
+
+    /// The first working day of the week.
+    pub first_workday: Weekday,
+}
+
+impl WeekInfo {
+    /// Returns the conventions of the C locale: weeks start on Sunday, but are numbered like in
+    /// ISO 8601 otherwise.
+    pub fn english() -> WeekInfo {
+        WeekInfo {
+            first_weekday: Weekday::Sunday,
+            min_days_in_first_week: 4,
+            first_workday: Weekday::Monday,
+        }
+    }
+
+    /// Returns the working days, in order from `first_workday`.
+    ///
+    /// Locales only define the first working day, so the working week is taken to be five days.
+    pub fn workdays(&self) -> Vec<Weekday> {
+        (0..5).map(|days| self.first_workday.add_days(days)).collect()
+    }
+
+    /// Returns whether the day is a working day.
+    pub fn is_workday(&self, day: Weekday) -> bool {
+        (day as i64 - self.first_workday as i64).rem_euclid(7) < 5
+    }
+
+    /// Returns the week-based year and number of the week the day falls in.
+    ///
+    /// Weeks start with `first_weekday` and the first week of a year is the first one that has
+    /// at least `min_days_in_first_week` days of the year, so a few days at the start or end of
+    /// a calendar year may belong to the neighbouring week-based year. With weeks starting on
+    /// Monday and 4 minimal days this gives ISO 8601 weeks.
+    pub fn week_of_year(&self, date: Date) -> (i32, u8) {
+        let days = date.days_since_epoch();
+        let mut year = date.year;
+        if days < self.first_week_start(year) {
+            year -= 1;
+        } else if days >= self.first_week_start(year + 1) {
+            year += 1;
+        }
+        (year, ((days - self.first_week_start(year)) / 7 + 1) as u8)
+    }
+
+    /// Returns the first day of the first week of the year as number of days since 1970-01-01.
+    fn first_week_start(&self, year: i32) -> i64 {
+        let jan1 = Date::new(year, 1, 1);
+        let days_in_week_before = (jan1.day_of_week() as i64 - self.first_weekday as i64)
+            .rem_euclid(7);
+        let start = jan1.days_since_epoch() - days_in_week_before;
+        let min_days = (self.min_days_in_first_week as i64).clamp(1, 7);
+        if 7 - days_in_week_before >= min_days { start } else { start + 7 }
+    }
+}
+
+/// How days are laid out in printed calendars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalendarDirection {
+    /// Weeks are rows with days from left to right.
+    LeftToRight,
+
+    /// Weeks are columns with days from top to bottom.
+    TopToBottom,
+
+    /// Weeks are rows with days from right to left.
+    RightToLeft,
+}
+
+/// Week numbering shown in calendars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeekNumbers {
+    /// ISO 8601 week numbers.
+    Iso,
+
+    /// Week numbers following `WeekInfo` of the locale.
+    Locale,
+}
+
+/// Week in a `MonthCalendar`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarWeek {
+    /// Week number, if requested.
+    pub number: Option<u8>,
+
+    /// Days of the month in order of `MonthCalendar::weekdays`, `None` for days of the
+    /// neighbouring months.
+    pub days: Vec<Option<u8>>,
+}
+
+/// Days of a month arranged in weeks like in printed calendars, see `Time::month_calendar`.
+///
+/// Formatting it with `Display` renders it as plain text in the layout of `cal`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonthCalendar {
+    /// Heading with the month name and year.
+    pub title: String,
+
+    /// Days of the week in the order of the calendar, starting with the first day of the week.
+    pub weekdays: Vec<Weekday>,
+
+    /// Abbreviated names of `weekdays`.
+    pub weekday_names: Vec<String>,
+
+    /// The weeks that have any days of the month.
+    pub weeks: Vec<CalendarWeek>,
+
+    /// How the weeks and days are to be laid out.
+    pub direction: CalendarDirection,
+}
+
+impl Display for MonthCalendar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // like `cal`, always show six weeks and day names in two columns
+        let numbered = self.weeks.iter().any(|week| week.number.is_some());
+        let blank = CalendarWeek { number: None, days: vec![None; self.weekdays.len()] };
+        let weeks: Vec<&CalendarWeek> = self.weeks.iter()
+            .chain(::std::iter::repeat(&blank))
+            .take(::std::cmp::max(6, self.weeks.len()))
+            .collect();
+        let names: Vec<String> = self.weekday_names.iter()
+            .map(|name| align_center(name, 2))
+            .collect();
+        let cell = |value: Option<u8>| value.map_or("  ".to_string(), |v| format!("{:2}", v));
+
+        if self.direction == CalendarDirection::TopToBottom {
+            writeln!(f, "{}", align_center(&self.title, 2 + 3 * weeks.len()))?;
+            for (index, name) in names.iter().enumerate() {
+                f.write_str(name)?;
+                for week in &weeks {
+                    write!(f, " {}", cell(week.days.get(index).cloned().unwrap_or(None)))?;
+                }
+                writeln!(f)?;
+            }
+            if numbered {
+                f.write_str("  ")?;
+                for week in &weeks {
+                    write!(f, " {}", cell(week.number))?;
+                }
+                writeln!(f)?;
+            }
+            return Ok(());
+        }
+
+        let rtl = self.direction == CalendarDirection::RightToLeft;
+        let write_row = |f: &mut fmt::Formatter, number: &str, mut cells: Vec<String>| {
+            if rtl {
+                cells.reverse();
+            }
+            match (numbered, rtl) {
+                (false, _) => writeln!(f, "{}", cells.join(" ")),
+                (true, false) => writeln!(f, "{} {}", number, cells.join(" ")),
+                (true, true) => writeln!(f, "{} {}", cells.join(" "), number),
+            }
+        };
+        let width = 3 * names.len() - 1 + if numbered { 3 } else { 0 };
+        writeln!(f, "{}", align_center(&self.title, width))?;
+        write_row(f, "  ", names)?;
+        for week in weeks {
+            write_row(f, &cell(week.number), week.days.iter().map(|&day| cell(day)).collect())?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns number of terminal columns the character takes: two for East Asian wide characters
+/// and none for combining marks.
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x0300..=0x036F | 0x0483..=0x0489 | 0x0591..=0x05BD | 0x0610..=0x061A
+            | 0x064B..=0x065F | 0x0E31 | 0x0E34..=0x0E3A | 0x0E47..=0x0E4E
+            | 0x200B..=0x200F | 0xFE00..=0xFE0F => 0,
+        0x1100..=0x115F | 0x2E80..=0x303E | 0x3041..=0xA4CF | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF | 0xFE30..=0xFE4F | 0xFF00..=0xFF60 | 0xFFE0..=0xFFE6
+            | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
+}
+
+/// Centers text in given number of terminal columns, cutting it off if it is too wide.
+fn align_center(text: &str, width: usize) -> String {
+    let mut fitted = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        if used + char_width(c) > width {
+            break;
+        }
+        used += char_width(c);
+        fitted.push(c);
+    }
+    let left = (width - used) / 2;
+    let mut res = String::new();
+    push_fill(&mut res, ' ', left);
+    res.push_str(&fitted);
+    push_fill(&mut res, ' ', width - used - left);
+    res
+}
+
+#[derive(Debug, Clone)]
+pub struct Time {
+    month_names: Vec<String>,
+    long_month_names: Vec<String>,
+    standalone_month_names: Vec<String>,
+    standalone_long_month_names: Vec<String>,
+    day_names: Vec<String>,
+    long_day_names: Vec<String>,
+    date_time_format: String,
+    date_format: String,
+    time_format: String,
+    time_ampm_format: String,
+    am: String,
+    pm: String,
+    era_date_time_format: String,
+    era_date_format: String,
+    era_time_format: String,
+    eras: Vec<Era>,
+    alt_digits: Vec<String>,
+    week_info: WeekInfo,
+    calendar_direction: CalendarDirection,
+}
+
+impl Time {
+    pub fn load_user_locale() -> Result<Time> {
+        if let Ok(mut factory) = SystemLocaleFactory::new("") {
+            if let Some(time) = factory.get_time() {
+                return Ok(*time);
+            }
+        }
+        Ok(Time::english())
+    }
+
+    pub fn english() -> Time {
+        let month_names = vec![
+            "Jan".to_string(),  "Feb".to_string(),  "Mar".to_string(),
+            "Apr".to_string(),  "May".to_string(),  "Jun".to_string(),
+            "Jul".to_string(),  "Aug".to_string(),  "Sep".to_string(),
+            "Oct".to_string(),  "Nov".to_string(),  "Dec".to_string(),
+        ];
+        let long_month_names = vec![
+            "January".to_string(),    "February".to_string(),
+            "March".to_string(),      "April".to_string(),
+            "May".to_string(),        "June".to_string(),
+            "July".to_string(),       "August".to_string(),
+            "September".to_string(),  "October".to_string(),
+            "November".to_string(),   "December".to_string(),
+        ];
+        Time {
+            standalone_month_names: month_names.clone(),
+            standalone_long_month_names: long_month_names.clone(),
+            month_names,
+            long_month_names,
+            day_names: vec![
+                "Sun".to_string(),
+                "Mon".to_string(),  "Tue".to_string(),  "Wed".to_string(),
+                "Thu".to_string(),  "Fri".to_string(),  "Sat".to_string(),
+            ],
+            long_day_names: vec![
+                "Sunday".to_string(),
+                "Monday".to_string(),    "Tuesday".to_string(),  "Wednesday".to_string(),
+                "Thursday".to_string(),  "Friday".to_string(),   "Saturday".to_string(),
+            ],
+            date_time_format: "%a %b %e %H:%M:%S %Y".to_string(),
+            date_format: "%m/%d/%y".to_string(),
+            time_format: "%H:%M:%S".to_string(),
+            time_ampm_format: "%I:%M:%S %p".to_string(),
+            am: "AM".to_string(),
+            pm: "PM".to_string(),
+            era_date_time_format: String::new(),
+            era_date_format: String::new(),
+            era_time_format: String::new(),
+            eras: Vec::new(),
+            alt_digits: Vec::new(),
+            week_info: WeekInfo::english(),
+            calendar_direction: CalendarDirection::LeftToRight,
+        }
+    }
+
+    /// Returns the full name of the month as used in dates, used for `%B`.
+    ///
+    /// In languages that decline month names this is usually the genitive form, like Polish
+    /// "5 marca".
+    pub fn long_month_name(&self, month: Month) -> &str {
+        time_name(&self.long_month_names, month as usize - 1)
+    }
+
+    /// Returns the abbreviated name of the month as used in dates, used for `%b`.
+    pub fn short_month_name(&self, month: Month) -> &str {
+        time_name(&self.month_names, month as usize - 1)
+    }
+
+    /// Returns the full name of the month standing on its own, like in calendar headings, used
+    /// for `%OB`.
+    ///
+    /// In languages that decline month names this is the nominative form, like Polish "marzec".
+    /// Same as `long_month_name` in other languages.
+    pub fn standalone_long_month_name(&self, month: Month) -> &str {
+        time_name(&self.standalone_long_month_names, month as usize - 1)
+    }
+
+    /// Returns the abbreviated name of the month standing on its own, used for `%Ob`.
+    pub fn standalone_short_month_name(&self, month: Month) -> &str {
+        time_name(&self.standalone_month_names, month as usize - 1)
+    }
+
+    /// Returns the full name of the day of the week, used for `%A`.
+    pub fn long_day_name(&self, day: Weekday) -> &str {
+        time_name(&self.long_day_names, day as usize)
+    }
+
+    /// Returns the abbreviated name of the day of the week, used for `%a`.
+    pub fn short_day_name(&self, day: Weekday) -> &str {
+        time_name(&self.day_names, day as usize)
+    }
+
+    /// Returns the pattern of the appropriate date and time representation, used for `%c`.
+    pub fn date_time_format(&self) -> &str {
+        &self.date_time_format
+    }
+
+    /// Returns the pattern of the appropriate date representation, used for `%x`.
+    pub fn date_format(&self) -> &str {
+        &self.date_format
+    }
+
+    /// Returns the pattern of the appropriate time representation, used for `%X`.
+    pub fn time_format(&self) -> &str {
+        &self.time_format
+    }
+
+    /// Returns the pattern of the time representation in 12-hour clock, used for `%r`.
+    ///
+    /// Empty if the locale does not use 12-hour clock.
+    pub fn time_ampm_format(&self) -> &str {
+        &self.time_ampm_format
+    }
+
+    /// Returns the string for times before noon, or after it if `pm` is set. Used for `%p`.
+    pub fn am_pm(&self, pm: bool) -> &str {
+        if pm { &self.pm } else { &self.am }
+    }
+
+    /// Returns the eras the locale counts years in besides the Gregorian calendar, in order of
+    /// precedence. Empty for most locales.
+    pub fn eras(&self) -> &[Era] {
+        &self.eras
+    }
+
+    /// Returns the first era the day falls within, the one used for `%EC`, `%Ey` and `%EY`.
+    pub fn era_at(&self, date: Date) -> Option<&Era> {
+        self.eras.iter().find(|era| era.contains(date))
+    }
+
+    /// Returns the alternative symbols for numbers from 0 up to 99 used by `%O` conversions,
+    /// indexed by the number. Empty for most locales.
+    pub fn alt_digits(&self) -> &[String] {
+        &self.alt_digits
+    }
+
+    /// Returns the conventions for weeks.
+    pub fn week_info(&self) -> &WeekInfo {
+        &self.week_info
+    }
+
+    /// Returns how days are laid out in printed calendars.
+    pub fn calendar_direction(&self) -> CalendarDirection {
+        self.calendar_direction
+    }
+
+    /// Arranges days of the month in weeks starting with the first day of the week of the
+    /// locale, with week numbers if requested.
+    ///
+    /// The week number of a week is that of its fourth day, so that weeks starting on another
+    /// day than Monday get the ISO 8601 number most of their days have.
+    pub fn month_calendar(&self, year: i32, month: Month, week_numbers: Option<WeekNumbers>)
+        -> MonthCalendar
+    {
+        let first_weekday = self.week_info.first_weekday;
+        let weekdays: Vec<Weekday> = (0..7).map(|days| first_weekday.add_days(days)).collect();
+        let first = Date::new(year, month.number(), 1).days_since_epoch();
+        let last = first + days_in_month(year, month.number()) as i64 - 1;
+        let mut start = first - (first + 4 - first_weekday as i64).rem_euclid(7);
+        let mut weeks = Vec::new();
+        while start <= last {
+            let days = (start..start + 7)
+                .map(|day| Some((day - first + 1) as u8).filter(|_| first <= day && day <= last))
+                .collect();
+            let fourth = Date::from_days_since_epoch(start + 3);
+            let number = week_numbers.map(|numbers| match numbers {
+                WeekNumbers::Iso => fourth.iso_week().1,
+                WeekNumbers::Locale => self.week_info.week_of_year(fourth).1,
+            });
+            weeks.push(CalendarWeek { number, days });
+            start += 7;
+        }
+        MonthCalendar {
+            title: format!("{} {}", self.standalone_long_month_name(month), year),
+            weekday_names: weekdays.iter()
+                .map(|&day| self.short_day_name(day).to_string())
+                .collect(),
+            weekdays,
+            weeks,
+            direction: self.calendar_direction,
+        }
+    }
+
+    /// Formats the time according to `pattern` like C `strftime` does.
+    ///
+    /// All conversions of GNU C library are supported, with the `_`, `-`, `0`, `^` and `#`
+    /// flags, field width and `E` and `O` modifiers. Conversions that are unknown or do not
+    /// accept the given modifier are copied to the output as they are.
+    ///
+    /// Unlike C, `%s` takes `utc_offset` of the time into account instead of the system time
+    /// zone, case conversion is not limited to ASCII in UTF-8 locales and field width counts
+    /// characters rather than bytes.
+    pub fn format(&self, pattern: &str, time: &DateTime) -> String {
+        let mut buf = String::new();
+        self.format_to(&mut buf, pattern, time, false);
+        buf
+    }
+
+    fn format_to(&self, buf: &mut String, pattern: &str, time: &DateTime, upcase: bool) {
+        let mut rest = pattern;
+        while let Some(pos) = rest.find('%') {
+            buf.push_str(&rest[..pos]);
+            let len = self.format_conversion(buf, &rest[pos..], time, upcase);
+            rest = &rest[pos + len..];
+        }
+        buf.push_str(rest);
+    }
+
+    /// Formats the conversion `spec` starts with and returns its length.
+    fn format_conversion(&self, buf: &mut String, spec: &str, time: &DateTime, upcase: bool)
+        -> usize
+    {
+        let bytes = spec.as_bytes();
+        let mut pos = 1;
+        let mut field = Field { pad: None, width: 0, alt_digits: &[] };
+        let (mut to_upper, mut change_case) = (upcase, false);
+        loop {
+            match bytes.get(pos) {
+                Some(&c @ b'_') | Some(&c @ b'-') | Some(&c @ b'0') => field.pad = Some(c),
+                Some(&b'^') => to_upper = true,
+                Some(&b'#') => change_case = true,
+                _ => break,
+            }
+            pos += 1;
+        }
+        while let Some(&c) = bytes.get(pos).filter(|c| c.is_ascii_digit()) {
+            field.width = field.width.saturating_mul(10).saturating_add((c - b'0') as usize);
+            pos += 1;
+        }
+        let modifier = match bytes.get(pos) {
+            Some(&c @ b'E') | Some(&c @ b'O') => {
+                pos += 1;
+                Some(c)
+            }
+            _ => None,
+        };
+        if modifier == Some(b'O') {
+            field.alt_digits = &self.alt_digits;
+        }
+        let conversion = spec[pos..].chars().next();
+        let len = pos + conversion.map_or(0, char::len_utf8);
+
+        let date = &time.date;
+        let day_of_week = date.day_of_week() as i64;
+        let day_of_year = date.day_of_year() as i64 - 1;
+        let hour12 = match time.hour % 12 { 0 => 12, hour => hour } as i64;
+        let month = (date.month as usize).wrapping_sub(1);
+        let ampm = self.am_pm(time.hour > 11);
+        let (e, o) = (modifier == Some(b'E'), modifier == Some(b'O'));
+        let era = if e { self.era_at(*date) } else { None };
+        match conversion {
+            Some('a') if modifier.is_none() => {
+                let text = time_name(&self.day_names, day_of_week as usize);
+                field.text(buf, &change_text_case(text, to_upper || change_case, false));
+            }
+            Some('A') if modifier.is_none() => {
+                let text = time_name(&self.long_day_names, day_of_week as usize);
+                field.text(buf, &change_text_case(text, to_upper || change_case, false));
+            }
+            Some('b') | Some('h') if !e => {
+                let names = if o { &self.standalone_month_names } else { &self.month_names };
+                let text = time_name(names, month);
+                field.text(buf, &change_text_case(text, to_upper || change_case, false));
+            }
+            Some('B') if !e => {
+                let names = if o {
+                    &self.standalone_long_month_names
+                } else {
+                    &self.long_month_names
+                };
+                let text = time_name(names, month);
+                field.text(buf, &change_text_case(text, to_upper || change_case, false));
+            }
+            Some('c') if !o => {
+                let pattern = if e && !self.era_date_time_format.is_empty() {
+                    &self.era_date_time_format
+                } else {
+                    &self.date_time_format
+                };
+                self.format_subpattern(buf, &field, pattern, time, to_upper);
+            }
+            Some('C') => match era {
+                Some(era) => field.text(buf, &change_text_case(&era.name, to_upper, false)),
+                None => field.number(buf, date.year.div_euclid(100) as i64, 1, b'0'),
+            },
+            Some('d') if !e => field.number(buf, date.day as i64, 2, b'0'),
+            Some('D') if modifier.is_none() => {
+                self.format_subpattern(buf, &field, "%m/%d/%y", time, to_upper);
+            }
+            Some('e') if !e => field.number(buf, date.day as i64, 2, b'_'),
+            Some('F') if modifier.is_none() => {
+                self.format_subpattern(buf, &field, "%Y-%m-%d", time, to_upper);
+            }
+            Some('g') if !e => field.number(buf, date.iso_week().0.rem_euclid(100) as i64, 2, b'0'),
+            Some('G') if !e => field.number(buf, date.iso_week().0 as i64, 1, b'0'),
+            Some('H') if !e => field.number(buf, time.hour as i64, 2, b'0'),
+            Some('I') if !e => field.number(buf, hour12, 2, b'0'),
+            Some('j') if !e => field.number(buf, day_of_year + 1, 3, b'0'),
+            Some('k') if !e => field.number(buf, time.hour as i64, 2, b'_'),
+            Some('l') if !e => field.number(buf, hour12, 2, b'_'),
+            Some('m') if !e => field.number(buf, date.month as i64, 2, b'0'),
+            Some('M') if !e => field.number(buf, time.minute as i64, 2, b'0'),
+            Some('n') => field.text(buf, "\n"),
+            Some('p') => {
+                field.text(buf, &change_text_case(ampm, to_upper && !change_case, change_case));
+            }
+            Some('P') => field.text(buf, &change_text_case(ampm, false, true)),
+            Some('r') => {
+                let pattern = if self.time_ampm_format.is_empty() {
+                    "%I:%M:%S %p"
+                } else {
+                    &self.time_ampm_format
+                };
+                self.format_subpattern(buf, &field, pattern, time, to_upper);
+            }
+            Some('R') => self.format_subpattern(buf, &field, "%H:%M", time, to_upper),
+            Some('s') => Field { alt_digits: &[], ..field }.number(buf, time.timestamp(), 1, b'0'),
+            Some('S') if !e => field.number(buf, time.second as i64, 2, b'0'),
+            Some('t') => field.text(buf, "\t"),
+            Some('T') => self.format_subpattern(buf, &field, "%H:%M:%S", time, to_upper),
+            Some('u') => field.number(buf, (day_of_week + 6) % 7 + 1, 1, b'0'),
+            Some('U') if !e => field.number(buf, (day_of_year - day_of_week + 7) / 7, 2, b'0'),
+            Some('V') if !e => field.number(buf, date.iso_week().1 as i64, 2, b'0'),
+            Some('w') if !e => field.number(buf, day_of_week, 1, b'0'),
+            Some('W') if !e => {
+                field.number(buf, (day_of_year - (day_of_week + 6) % 7 + 7) / 7, 2, b'0');
+            }
+            Some('x') if !o => {
+                let pattern = if e && !self.era_date_format.is_empty() {
+                    &self.era_date_format
+                } else {
+                    &self.date_format
+                };
+                self.format_subpattern(buf, &field, pattern, time, to_upper);
+            }
+            Some('X') if !o => {
+                let pattern = if e && !self.era_time_format.is_empty() {
+                    &self.era_time_format
+                } else {
+                    &self.time_format
+                };
+                self.format_subpattern(buf, &field, pattern, time, to_upper);
+            }
+            Some('y') => {
+                let year = match era {
+                    Some(era) => era.year_of(*date),
+                    None => date.year.rem_euclid(100),
+                };
+                field.number(buf, year as i64, 2, b'0');
+            }
+            Some('Y') if !o => match era {
+                Some(era) => {
+                    // padding flag is passed to the year in era like in GNU C library
+                    let pattern = match field.pad {
+                        Some(pad) => {
+                            Cow::Owned(era.format.replace("%Ey", &format!("%{}Ey", pad as char)))
+                        }
+                        None => Cow::Borrowed(&era.format[..]),
+                    };
+                    self.format_subpattern(buf, &field, &pattern, time, to_upper);
+                }
+                None => field.number(buf, date.year as i64, 1, b'0'),
+            },
+            Some('z') => {
+                if let Some(offset) = time.utc_offset {
+                    field.text(buf, if offset < 0 { "-" } else { "+" });
+                    let minutes = (offset as i64).abs() / 60;
+                    field.number(buf, minutes / 60 * 100 + minutes % 60, 4, b'0');
+                }
+            }
+            Some('Z') => {
+                let text = change_text_case(&time.zone, to_upper && !change_case, change_case);
+                field.text(buf, &text);
+            }
+            Some('%') => field.text(buf, "%"),
+            _ => {
+                // the case flags apply to the copy, with `#` having its effect before the
+                // modifier is rejected
+                let upper = to_upper || change_case
+                    && matches!(conversion, Some('A') | Some('b') | Some('B') | Some('h'));
+                field.text(buf, &change_text_case(&spec[..len], upper, false));
+            }
+        }
+        len
+    }
+
+    fn format_subpattern(&self, buf: &mut String, field: &Field, pattern: &str, time: &DateTime,
+                         upcase: bool) {
+        let mut text = String::new();
+        self.format_to(&mut text, pattern, time, upcase);
+        if upcase {
+            text = text.to_uppercase();
+        }
+        field.text(buf, &text);
+    }
+
+    /// Parses time written according to `pattern` like C `strptime` does.
+    ///
+    /// Month and day names are matched in both full and abbreviated form ignoring case and so
+    /// are the AM/PM strings. Whitespace in the pattern matches any amount of whitespace in the
+    /// input, including none, and numbers may be preceded by whitespace. Flags and field width
+    /// in conversions are ignored, so the patterns `format` accepts can be used and what it
+    /// produces is parsed back, as far as the pattern carries the information.
+    ///
+    /// Fields missing from the input are taken from 1970-01-01 00:00:00. The date may also be
+    /// given by day of the year (`%j`), week number and day of the week (`%U` or `%W` with
+    /// `%a` or `%w`) or ISO 8601 week date (`%G`, `%V` and `%u`). Years are limited to four
+    /// digits and optional minus sign. Years without century (`%y`) from 69 on fall into the
+    /// 20th century, the others into the 21st.
+    ///
+    /// In locales with eras, `%EC`, `%Ey` and `%EY` read the era name and year. Year in era
+    /// without the era name is counted in the first era of the locale, usually the current one.
+    /// Numbers of `%O` conversions may be written in alternative digits of the locale.
+    pub fn parse(&self, pattern: &str, input: &str)
+        -> ::std::result::Result<DateTime, ParseTimeError>
+    {
+        let mut fields = ParsedTime::default();
+        let start = input.len() - input.trim_start().len();
+        let end = self.parse_fields(pattern, input, start, &mut fields)?;
+        if !input[end..].trim_end().is_empty() {
+            return Err(ParseTimeError::new(ParseTimeErrorKind::TrailingCharacters, end));
+        }
+        fields.to_date_time(&self.eras, input.len())
+    }
+
+    /// Parses `input` from `pos` according to `pattern` and returns where it ended.
+    fn parse_fields(&self, pattern: &str, input: &str, mut pos: usize, fields: &mut ParsedTime)
+        -> ::std::result::Result<usize, ParseTimeError>
+    {
+        use ParseTimeErrorKind::*;
+
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c.is_whitespace() {
+                pos = skip_whitespace(input, pos);
+                continue;
+            }
+            if c != '%' {
+                if !input[pos..].starts_with(c) {
+                    return Err(ParseTimeError::new(Mismatch, pos));
+                }
+                pos += c.len_utf8();
+                continue;
+            }
+
+            // flags, width and modifiers only matter for formatting
+            let spec = chars.as_str()
+                .trim_start_matches(|c: char| "_-0^#".contains(c) || c.is_ascii_digit());
+            let e = spec.starts_with('E');
+            let era = e && !self.eras.is_empty();
+            let alt_digits = if spec.starts_with('O') { &self.alt_digits[..] } else { &[] };
+            let number = |pos: &mut usize, digits, min, max| {
+                match parse_alt_number(input, pos, alt_digits, min, max) {
+                    Some(result) => result,
+                    None => parse_time_number(input, pos, digits, min, max),
+                }
+            };
+            let spec = spec.strip_prefix(|c| c == 'E' || c == 'O').unwrap_or(spec);
+            chars = spec.chars();
+            let subpattern = match chars.next() {
+                Some('C') if era => {
+                    let names: Vec<&str> = self.eras.iter().map(|era| &era.name[..]).collect();
+                    fields.era = Some(match_name(input, &mut pos, &[&names[..]])?);
+                    continue;
+                }
+                Some('y') if era => {
+                    fields.era_year = Some(number(&mut pos, 4, 0, 9999)? as i32);
+                    continue;
+                }
+                Some('Y') if era => {
+                    pos = self.parse_era_year(input, pos, fields)?;
+                    continue;
+                }
+                Some('a') | Some('A') => {
+                    let names = [&self.long_day_names[..], &self.day_names[..]];
+                    fields.day_of_week = Some(match_name(input, &mut pos, &names)? as u8);
+                    continue;
+                }
+                Some('b') | Some('B') | Some('h') => {
+                    let names = [&self.long_month_names[..], &self.month_names[..],
+                                 &self.standalone_long_month_names[..],
+                                 &self.standalone_month_names[..]];
+                    fields.month = Some(match_name(input, &mut pos, &names)? as u8 + 1);
+                    continue;
+                }
+                Some('c') if e && !self.era_date_time_format.is_empty() => {
+                    &self.era_date_time_format[..]
+                }
+                Some('c') => &self.date_time_format[..],
+                Some('C') => {
+                    let century = parse_alt_number(input, &mut pos, alt_digits, 0, 99);
+                    fields.century = Some(match century {
+                        Some(result) => result? as i32,
+                        None => parse_time_year(input, &mut pos, 2)?,
+                    });
+                    continue;
+                }
+                Some('d') | Some('e') => {
+                    fields.day = Some(number(&mut pos, 2, 1, 31)? as u8);
+                    continue;
+                }
+                Some('D') => "%m/%d/%y",
+                Some('F') => "%Y-%m-%d",
+                Some('g') => {
+                    fields.iso_year_of_century = Some(number(&mut pos, 2, 0, 99)? as i32);
+                    continue;
+                }
+                Some('G') => {
+                    fields.iso_year = Some(parse_time_year(input, &mut pos, 4)?);
+                    continue;
+                }
+                Some('H') | Some('k') => {
+                    fields.hour = Some(number(&mut pos, 2, 0, 23)? as u8);
+                    continue;
+                }
+                Some('I') | Some('l') => {
+                    fields.hour12 = Some(number(&mut pos, 2, 1, 12)? as u8);
+                    continue;
+                }
+                Some('j') => {
+                    fields.day_of_year = Some(number(&mut pos, 3, 1, 366)? as u16);
+                    continue;
+                }
+                Some('m') => {
+                    fields.month = Some(number(&mut pos, 2, 1, 12)? as u8);
+                    continue;
+                }
+                Some('M') => {
+                    fields.minute = Some(number(&mut pos, 2, 0, 59)? as u8);
+                    continue;
+                }
+                Some('n') | Some('t') => {
+                    pos = skip_whitespace(input, pos);
+                    continue;
+                }
+                Some('p') | Some('P') => {
+                    // locales without 12-hour clock have empty strings
+                    let names = [&self.am[..], &self.pm[..]];
+                    fields.pm = Some(match match_name(input, &mut pos, &[&names[..]]) {
+                        Ok(index) => index == 1,
+                        Err(_) if self.am.is_empty() => false,
+                        Err(_) if self.pm.is_empty() => true,
+                        Err(e) => return Err(e),
+                    });
+                    continue;
+                }
+                Some('r') if self.time_ampm_format.is_empty() => "%I:%M:%S %p",
+                Some('r') => &self.time_ampm_format[..],
+                Some('R') => "%H:%M",
+                Some('s') => {
+                    fields.timestamp = Some(parse_timestamp(input, &mut pos)?);
+                    continue;
+                }
+                Some('S') => {
+                    fields.second = Some(number(&mut pos, 2, 0, 60)? as u8);
+                    continue;
+                }
+                Some('T') => "%H:%M:%S",
+                Some('u') => {
+                    fields.day_of_week = Some(number(&mut pos, 1, 1, 7)? as u8 % 7);
+                    continue;
+                }
+                Some('U') => {
+                    fields.sunday_week = Some(number(&mut pos, 2, 0, 53)? as u8);
+                    continue;
+                }
+                Some('V') => {
+                    fields.iso_week = Some(number(&mut pos, 2, 1, 53)? as u8);
+                    continue;
+                }
+                Some('w') => {
+                    fields.day_of_week = Some(number(&mut pos, 1, 0, 6)? as u8);
+                    continue;
+                }
+                Some('W') => {
+                    fields.monday_week = Some(number(&mut pos, 2, 0, 53)? as u8);
+                    continue;
+                }
+                Some('x') if e && !self.era_date_format.is_empty() => &self.era_date_format[..],
+                Some('x') => &self.date_format[..],
+                Some('X') if e && !self.era_time_format.is_empty() => &self.era_time_format[..],
+                Some('X') => &self.time_format[..],
+                Some('y') => {
+                    fields.year_of_century = Some(number(&mut pos, 2, 0, 99)? as i32);
+                    continue;
+                }
+                Some('Y') => {
+                    fields.year = Some(parse_time_year(input, &mut pos, 4)?);
+                    continue;
+                }
+                Some('z') => {
+                    fields.utc_offset = Some(parse_utc_offset(input, &mut pos)?);
+                    continue;
+                }
+                Some('Z') => {
+                    let start = skip_whitespace(input, pos);
+                    pos = input[start..].find(char::is_whitespace)
+                        .map_or(input.len(), |len| start + len);
+                    fields.zone = input[start..pos].to_string();
+                    continue;
+                }
+                Some('%') => {
+                    if !input[pos..].starts_with('%') {
+                        return Err(ParseTimeError::new(Mismatch, pos));
+                    }
+                    pos += 1;
+                    continue;
+                }
+                _ => return Err(ParseTimeError::new(InvalidPattern, pos)),
+            };
+            pos = self.parse_fields(subpattern, input, pos, fields)?;
+        }
+        Ok(pos)
+    }
+
+    /// Parses `%EY` by trying the formats of all eras, as each may be different.
+    fn parse_era_year(&self, input: &str, pos: usize, fields: &mut ParsedTime)
+        -> ::std::result::Result<usize, ParseTimeError>
+    {
+        let mut error = ParseTimeError::new(ParseTimeErrorKind::Mismatch, pos);
+        for (index, era) in self.eras.iter().enumerate() {
+            let mut era_fields = ParsedTime::default();
+            let result = self.parse_fields(&era.format, input, pos, &mut era_fields);
+            // the era name is matched to the first era of that name, which need not be the one
+            // whose format matched
+            let other_era = era_fields.era.filter(|&i| self.eras[i].name != era.name);
+            match result {
+                Ok(_) if other_era.is_some() => {}
+                Ok(end) => {
+                    fields.era = Some(index);
+                    fields.era_year = era_fields.era_year;
+                    return Ok(end);
+                }
+                Err(e) => if e.position > error.position { error = e },
+            }
+        }
+        Err(error)
+    }
+}
+
+
+/// Padding of a `strftime` conversion.
+struct Field<'a> {
+    /// The padding flag, `_`, `-` or `0`.
+    pad: Option<u8>,
+    width: usize,
+    /// Alternative digits for `%O` conversions, empty for others.
+    alt_digits: &'a [String],
+}
+
+impl<'a> Field<'a> {
+    fn text(&self, buf: &mut String, text: &str) {
+        let fill = if self.pad == Some(b'0') { '0' } else { ' ' };
+        push_fill(buf, fill, self.width.saturating_sub(text.chars().count()));
+        buf.push_str(text);
+    }
+
+    /// Formats number padded to `digits` unless the flags say otherwise.
+    ///
+    /// Padding with zeros goes between the sign and digits, with spaces before the sign.
+    fn number(&self, buf: &mut String, value: i64, digits: usize, default_pad: u8) {
+        // alternative digits are only defined for numbers up to 99 and they are not padded
+        if value >= 0 && value < self.alt_digits.len() as i64
+            && !self.alt_digits[value as usize].is_empty()
+        {
+            return self.text(buf, &self.alt_digits[value as usize]);
+        }
+        let sign = if value < 0 { "-" } else { "" };
+        let number = (value.wrapping_abs() as u64).to_string();
+        let padding = ::std::cmp::max(digits, self.width)
+            .saturating_sub(sign.len() + number.len());
+        match self.pad.unwrap_or(default_pad) {
+            b'-' => self.text(buf, &format!("{}{}", sign, number)),
+            b'_' => {
+                push_fill(buf, ' ', padding);
+                buf.push_str(sign);
+                buf.push_str(&number);
+            }
+            _ => {
+                buf.push_str(sign);
+                push_fill(buf, '0', padding);
+                buf.push_str(&number);
+            }
+        }
+    }
+}
+
+/// Returns name from the list of month or day names, or `?` like C library if it is missing.
+fn time_name(names: &[String], index: usize) -> &str {
+    names.get(index).map_or("?", String::as_str)
+}
+
+fn push_fill(buf: &mut String, fill: char, count: usize) {
+    for _ in 0..count {
+        buf.push(fill);
+    }
+}
+
+fn change_text_case<'a>(text: &'a str, upper: bool, lower: bool) -> Cow<'a, str> {
+    if lower {
+        Cow::Owned(text.to_lowercase())
+    } else if upper {
+        Cow::Owned(text.to_uppercase())
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Kind of error encountered when parsing time with `Time::parse`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseTimeErrorKind {
+    /// The input does not match the pattern: a literal character, name or number is missing.
+    Mismatch,
+    /// A number is out of range for its field.
+    OutOfRange,
+    /// There are unexpected characters after the input matched the whole pattern.
+    TrailingCharacters,
+    /// The fields do not make up a valid date, like February 30th.
+    InvalidDate,
+    /// The pattern contains a conversion that can't be parsed.
+    InvalidPattern,
+}
+
+/// Error returned from `Time::parse`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTimeError {
+    kind: ParseTimeErrorKind,
+    position: usize,
+}
+
+impl ParseTimeError {
+    fn new(kind: ParseTimeErrorKind, position: usize) -> Self {
+        ParseTimeError { kind, position }
+    }
+
+    /// Returns what went wrong.
+    pub fn kind(&self) -> ParseTimeErrorKind {
+        self.kind
+    }
+
+    /// Returns byte offset in the input at which the problem was detected.
+    ///
+    /// Invalid date is only detected after reading the whole input.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl Display for ParseTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let what = match self.kind {
+            ParseTimeErrorKind::Mismatch => "input does not match pattern",
+            ParseTimeErrorKind::OutOfRange => "time field out of range",
+            ParseTimeErrorKind::TrailingCharacters => "unexpected characters after time",
+            ParseTimeErrorKind::InvalidDate => "invalid date",
+            ParseTimeErrorKind::InvalidPattern => "unsupported conversion in pattern",
+        };
+        write!(f, "{} at position {}", what, self.position)
+    }
+}
+
+impl Error for ParseTimeError {}
+
+/// Fields collected by `Time::parse`.
+#[derive(Default)]
+struct ParsedTime {
+    year: Option<i32>,
+    century: Option<i32>,
+    year_of_century: Option<i32>,
+    month: Option<u8>,
+    day: Option<u8>,
+    day_of_year: Option<u16>,
+    day_of_week: Option<u8>,
+    sunday_week: Option<u8>,
+    monday_week: Option<u8>,
+    iso_year: Option<i32>,
+    iso_year_of_century: Option<i32>,
+    iso_week: Option<u8>,
+    hour: Option<u8>,
+    hour12: Option<u8>,
+    pm: Option<bool>,
+    minute: Option<u8>,
+    second: Option<u8>,
+    era: Option<usize>,
+    era_year: Option<i32>,
+    timestamp: Option<i64>,
+    utc_offset: Option<i32>,
+    zone: String,
+}
+
+impl ParsedTime {
+    fn to_date_time(&self, eras: &[Era], end: usize)
+        -> ::std::result::Result<DateTime, ParseTimeError>
+    {
+        // years without century from 69 on are in the 20th century like in C
+        let full_year = |year_of_century| {
+            year_of_century + if year_of_century < 69 { 2000 } else { 1900 }
+        };
+        let iso_year = self.iso_year.or(self.iso_year_of_century.map(full_year));
+        // year in era without the era name is taken to be in the current era
+        let era = match (self.era, self.era_year) {
+            (None, None) => None,
+            (index, _) => eras.get(index.unwrap_or(0)),
+        };
+        let year = match (era, self.year, self.century, self.year_of_century) {
+            (Some(era), _, _, _) => era.gregorian_year(self.era_year.unwrap_or(era.offset)),
+            (None, Some(year), _, _) => year,
+            (None, None, Some(century), year_of_century) => {
+                century * 100 + year_of_century.unwrap_or(0)
+            }
+            (None, None, None, Some(year_of_century)) => full_year(year_of_century),
+            (None, None, None, None) => iso_year.unwrap_or(1970),
+        };
+        let jan1 = Date::new(year, 1, 1);
+
+        let mut date = if let Some(day) = self.day {
+            let date = Date::new(year, self.month.unwrap_or(1), day);
+            if date.day > days_in_month(year, date.month) {
+                return Err(ParseTimeError::new(ParseTimeErrorKind::InvalidDate, end));
+            }
+            date
+        } else if let Some(day_of_year) = self.day_of_year {
+            if day_of_year as i32 > days_in_year(year) {
+                return Err(ParseTimeError::new(ParseTimeErrorKind::InvalidDate, end));
+            }
+            Date::from_days_since_epoch(jan1.days_since_epoch() + day_of_year as i64 - 1)
+        } else if let Some(week) = self.sunday_week.or(self.monday_week) {
+            // week 1 starts with the first Sunday (%U) or Monday (%W) of the year
+            let first = if self.sunday_week.is_some() { 0 } else { 1 };
+            let first_day = (7 + first - jan1.day_of_week() as i64) % 7;
+            let day_of_week = (self.day_of_week.map_or(first, i64::from) + 7 - first) % 7;
+            Date::from_days_since_epoch(jan1.days_since_epoch() + first_day
+                                        + (week as i64 - 1) * 7 + day_of_week)
+        } else if let (Some(iso_year), Some(week)) = (iso_year, self.iso_week) {
+            // week 1 is the one with January 4th
+            let jan4 = Date::new(iso_year, 1, 4);
+            let monday = jan4.days_since_epoch() - (jan4.day_of_week() as i64 + 6) % 7;
+            let day_of_week = (self.day_of_week.unwrap_or(1) as i64 + 6) % 7;
+            Date::from_days_since_epoch(monday + (week as i64 - 1) * 7 + day_of_week)
+        } else {
+            Date::new(year, self.month.unwrap_or(1), 1)
+        };
+
+        let mut hour = self.hour.unwrap_or(0);
+        if let Some(hour12) = self.hour12 {
+            hour = hour12 % 12 + if self.pm == Some(true) { 12 } else { 0 };
+        }
+        let (mut minute, mut second) = (self.minute.unwrap_or(0), self.second.unwrap_or(0));
+        if let Some(timestamp) = self.timestamp {
+            let local = timestamp.checked_add(self.utc_offset.unwrap_or(0) as i64)
+                .filter(|local| (MIN_TIMESTAMP..=MAX_TIMESTAMP).contains(local))
+                .ok_or_else(|| ParseTimeError::new(ParseTimeErrorKind::OutOfRange, end))?;
+            date = Date::from_days_since_epoch(local.div_euclid(86400));
+            let time = local.rem_euclid(86400);
+            hour = (time / 3600) as u8;
+            minute = (time / 60 % 60) as u8;
+            second = (time % 60) as u8;
+        }
+        Ok(DateTime {
+            date,
+            hour,
+            minute,
+            second,
+            utc_offset: self.utc_offset,
+            zone: self.zone.clone(),
+        })
+    }
+}
+
+fn skip_whitespace(input: &str, pos: usize) -> usize {
+    input.len() - input[pos..].trim_start().len()
+}
+
+/// Reads number of at most `digits` digits and checks it is between `min` and `max`.
+fn parse_time_number(input: &str, pos: &mut usize, digits: usize, min: i64, max: i64)
+    -> ::std::result::Result<i64, ParseTimeError>
+{
+    let start = skip_whitespace(input, *pos);
+    let bytes = &input.as_bytes()[start..];
+    let mut value = 0;
+    let mut len = 0;
+    while len < digits && len < bytes.len() && bytes[len].is_ascii_digit() {
+        value = value * 10 + (bytes[len] - b'0') as i64;
+        len += 1;
+    }
+    if len == 0 {
+        return Err(ParseTimeError::new(ParseTimeErrorKind::Mismatch, start));
+    }
+    if value < min || value > max {
+        return Err(ParseTimeError::new(ParseTimeErrorKind::OutOfRange, start));
+    }
+    *pos = start + len;
+    Ok(value)
+}
+
+/// Reads number written in alternative digits, if there is one, and checks it is between
+/// `min` and `max`.
+fn parse_alt_number(input: &str, pos: &mut usize, alt_digits: &[String], min: i64, max: i64)
+    -> Option<::std::result::Result<i64, ParseTimeError>>
+{
+    let start = skip_whitespace(input, *pos);
+    let mut end = start;
+    let value = match_name(input, &mut end, &[alt_digits]).ok()? as i64;
+    if value < min || value > max {
+        return Some(Err(ParseTimeError::new(ParseTimeErrorKind::OutOfRange, start)));
+    }
+    *pos = end;
+    Some(Ok(value))
+}
+
+/// Reads year or century with optional minus sign.
+fn parse_time_year(input: &str, pos: &mut usize, digits: usize)
+    -> ::std::result::Result<i32, ParseTimeError>
+{
+    let start = skip_whitespace(input, *pos);
+    let negative = input[start..].starts_with('-');
+    let mut digits_pos = start + if negative { 1 } else { 0 };
+    let max = 10i64.pow(digits as u32) - 1;
+    let value = parse_time_number(input, &mut digits_pos, digits, 0, max)
+        .map_err(|e| ParseTimeError::new(e.kind, start))?;
+    *pos = digits_pos;
+    Ok(if negative { -value } else { value } as i32)
+}
+
+fn parse_timestamp(input: &str, pos: &mut usize) -> ::std::result::Result<i64, ParseTimeError> {
+    let start = skip_whitespace(input, *pos);
+    let sign = if input[start..].starts_with('-') { 1 } else { 0 };
+    let end = start + sign + input[start + sign..].bytes().take_while(u8::is_ascii_digit).count();
+    let value = input[start..end].parse().map_err(|_| {
+        let kind = if end > start + sign {
+            ParseTimeErrorKind::OutOfRange
+        } else {
+            ParseTimeErrorKind::Mismatch
+        };
+        ParseTimeError::new(kind, start)
+    })?;
+    *pos = end;
+    Ok(value)
+}
+
+/// Reads UTC offset as `Z`, `+hh`, `+hhmm` or `+hh:mm` and returns it in seconds.
+fn parse_utc_offset(input: &str, pos: &mut usize) -> ::std::result::Result<i32, ParseTimeError> {
+    let start = skip_whitespace(input, *pos);
+    let rest = &input[start..];
+    if rest.starts_with('Z') || rest.starts_with('z') {
+        *pos = start + 1;
+        return Ok(0);
+    }
+    let sign = match rest.chars().next() {
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return Err(ParseTimeError::new(ParseTimeErrorKind::Mismatch, start)),
+    };
+    let digits = |from: usize| input[from..].bytes().take_while(u8::is_ascii_digit).count();
+    let mut end = start + 1;
+    let (hours, minutes) = match digits(end) {
+        2 => {
+            let hours = &input[end..end + 2];
+            end += 2;
+            let minutes = if input[end..].starts_with(':') && digits(end + 1) == 2 {
+                end += 3;
+                &input[end - 2..end]
+            } else {
+                "0"
+            };
+            (hours, minutes)
+        }
+        4 => {
+            end += 4;
+            (&input[end - 4..end - 2], &input[end - 2..end])
+        }
+        _ => return Err(ParseTimeError::new(ParseTimeErrorKind::Mismatch, start)),
+    };
+    let (hours, minutes): (i32, i32) = (hours.parse().unwrap(), minutes.parse().unwrap());
+    if hours > 24 || minutes > 59 {
+        return Err(ParseTimeError::new(ParseTimeErrorKind::OutOfRange, start));
+    }
+    *pos = end;
+    Ok(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Matches the longest of `names` at the start of input ignoring case and returns its index
+/// in the list it was found in.
+fn match_name<S: AsRef<str>>(input: &str, pos: &mut usize, names: &[&[S]])
+    -> ::std::result::Result<usize, ParseTimeError>
+{
+    let rest = &input[*pos..];
+    let mut best: Option<(usize, usize)> = None;
+    for list in names {
+        for (index, name) in list.iter().enumerate() {
+            let name = name.as_ref();
+            let len = match rest.char_indices().nth(name.chars().count()) {
+                Some((len, _)) => len,
+                None => rest.len(),
+            };
+            let longer = match best {
+                Some((_, best_len)) => len > best_len,
+                None => true,
+            };
+            if !name.is_empty() && longer && rest[..len].to_lowercase() == name.to_lowercase() {
+                best = Some((index, len));
+            }
+        }
+    }
+    match best {
+        Some((index, len)) => {
+            *pos += len;
+            Ok(index)
+        }
+        None => Err(ParseTimeError::new(ParseTimeErrorKind::Mismatch, *pos)),
+    }
+}
+
+// ---- relative time stuff ----
+
+/// Unit of time used for formatting durations and relative times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TimeUnit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl TimeUnit {
+    /// All units, from the shortest to the longest.
+    pub fn all() -> [TimeUnit; 7] {
+        [TimeUnit::Second, TimeUnit::Minute, TimeUnit::Hour, TimeUnit::Day, TimeUnit::Week,
+         TimeUnit::Month, TimeUnit::Year]
+    }
+
+    /// Length of the unit in seconds.
+    ///
+    /// Months are counted as 30 days and years as 365 days, which is good enough for choosing
+    /// the unit to express an approximate time in.
+    pub fn seconds(self) -> u64 {
+        match self {
+            TimeUnit::Second => 1,
+            TimeUnit::Minute => 60,
+            TimeUnit::Hour => 3600,
+            TimeUnit::Day => 86400,
+            TimeUnit::Week => 7 * 86400,
+            TimeUnit::Month => 30 * 86400,
+            TimeUnit::Year => 365 * 86400,
+        }
+    }
+}
+
+/// Width of time unit names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitStyle {
+    /// Full names, like "3 hours".
+    Long,
+    /// Abbreviated names, like "3 hr".
+    Short,
+    /// The shortest form, like "3h".
+    Narrow,
+}
+
+/// Patterns for amounts of one time unit in one style.
+///
+/// Each list contains a pattern for every plural form of the language, in the order given by
+/// `RelativeTime::plural_rule`, with `{0}` standing for the number.
+#[derive(Debug, Clone, Default)]
+pub struct UnitPatterns {
+    /// Patterns for a duration, like "{0} hours".
+    pub duration: Vec<String>,
+
+    /// Patterns for a time in the past, like "{0} hours ago".
+    pub past: Vec<String>,
+
+    /// Patterns for a time in the future, like "in {0} hours".
+    pub future: Vec<String>,
+}
+
+/// Information on how to format durations and times relative to now.
+#[derive(Debug, Clone)]
+pub struct RelativeTime {
+    /// Patterns for each unit and style. Missing styles fall back to `UnitStyle::Long` and units
+    /// missing altogether are formatted as just the number.
+    pub units: HashMap<(TimeUnit, UnitStyle), UnitPatterns>,
+
+    /// Selects the plural form for a number, as index to the lists in `UnitPatterns`.
+    pub plural_rule: PluralRule,
+
+    /// The word for a relative time of zero, like "now".
+    pub now: String,
+
+    /// The punctuation that separates parts of a duration, like the space in "3 hours 5 minutes".
+    pub separator: String,
+}
+
+impl RelativeTime {
+    pub fn load_user_locale() -> Result<RelativeTime> {
+        if let Ok(mut factory) = SystemLocaleFactory::new("") {
+            if let Some(relative_time) = factory.get_relative_time() {
+                return Ok(*relative_time);
+            }
+        }
+        Ok(RelativeTime::english())
+    }
+
+    pub fn english() -> RelativeTime {
+        let names = [
+            (TimeUnit::Second, ["second", "sec", "s"]),
+            (TimeUnit::Minute, ["minute", "min", "m"]),
+            (TimeUnit::Hour, ["hour", "hr", "h"]),
+            (TimeUnit::Day, ["day", "day", "d"]),
+            (TimeUnit::Week, ["week", "wk", "w"]),
+            (TimeUnit::Month, ["month", "mo", "mo"]),
+            (TimeUnit::Year, ["year", "yr", "y"]),
+        ];
+        let mut units = HashMap::new();
+        for &(unit, [long, short, narrow]) in &names {
+            let forms = [
+                (UnitStyle::Long, [format!("{{0}} {}", long), format!("{{0}} {}s", long)]),
+                (UnitStyle::Short, [format!("{{0}} {}", short), format!("{{0}} {}s", short)]),
+                (UnitStyle::Narrow, [format!("{{0}}{}", narrow), format!("{{0}}{}", narrow)]),
+            ];
+            for (style, duration) in forms.iter().cloned() {
+                // Abbreviations of the shorter units are the same in plural.
+                let duration = if style == UnitStyle::Short && unit < TimeUnit::Day {
+                    vec![duration[0].clone(), duration[0].clone()]
+                } else {
+                    duration.to_vec()
+                };
+                units.insert((unit, style), UnitPatterns {
+                    past: duration.iter().map(|d| format!("{} ago", d)).collect(),
+                    future: duration.iter().map(|d| format!("in {}", d)).collect(),
+                    duration,
+                });
+            }
+        }
+        RelativeTime {
+            units,
+            plural_rule: PluralRule::germanic(),
+            now: "now".to_string(),
+            separator: " ".to_string(),
+        }
+    }
+
+    /// Formats a number of units as a duration, like "3 hours".
+    pub fn format_amount(&self, value: u64, unit: TimeUnit, style: UnitStyle) -> String {
+        self.apply(self.patterns(unit, style).map(|p| &p.duration[..]), value)
+    }
+
+    /// Formats a duration as days, hours, minutes and seconds, like "3 hours 5 minutes".
+    ///
+    /// Parts that are zero are left out and fractions of a second are ignored.
+    pub fn format_duration(&self, duration: ::std::time::Duration, style: UnitStyle) -> String {
+        let mut remaining = duration.as_secs();
+        let mut parts = Vec::new();
+        for &unit in &[TimeUnit::Day, TimeUnit::Hour, TimeUnit::Minute, TimeUnit::Second] {
+            let value = remaining / unit.seconds();
+            remaining %= unit.seconds();
+            if value > 0 {
+                parts.push(self.format_amount(value, unit, style));
+            }
+        }
+        if parts.is_empty() {
+            return self.format_amount(0, TimeUnit::Second, style);
+        }
+        parts.join(&self.separator)
+    }
+
+    /// Formats a number of units relative to now, like "2 days ago" for -2 or "in 5 minutes"
+    /// for 5.
+    pub fn format_relative_amount(&self, value: i64, unit: TimeUnit, style: UnitStyle) -> String {
+        let patterns = self.patterns(unit, style)
+            .map(|p| if value < 0 { &p.past[..] } else { &p.future[..] });
+        self.apply(patterns, value.unsigned_abs())
+    }
+
+    /// Formats an offset from now in seconds in the largest unit it contains at least once,
+    /// like "2 days ago" or "in 5 minutes". The amount is rounded towards zero.
+    pub fn format_relative(&self, seconds: i64, style: UnitStyle) -> String {
+        if seconds == 0 {
+            return self.now.clone();
+        }
+        let abs = seconds.unsigned_abs();
+        let unit = TimeUnit::all().iter().rev().cloned()
+            .find(|unit| abs >= unit.seconds())
+            .unwrap_or(TimeUnit::Second);
+        let value = (abs / unit.seconds()) as i64;
+        self.format_relative_amount(if seconds < 0 { -value } else { value }, unit, style)
+    }
+
+    fn patterns(&self, unit: TimeUnit, style: UnitStyle) -> Option<&UnitPatterns> {
+        self.units.get(&(unit, style)).or_else(|| self.units.get(&(unit, UnitStyle::Long)))
+    }
+
+    fn apply(&self, patterns: Option<&[String]>, value: u64) -> String {
+        match patterns {
+            Some(patterns) if !patterns.is_empty() => {
+                let form = self.plural_rule.form(value).min(patterns.len() - 1);
+                patterns[form].replace("{0}", &value.to_string())
+            }
+            _ => value.to_string(),
+        }
+    }
+}
+
+// ---- messages stuff ----
+
+/// Information on how to recognize answers to yes/no questions.
+#[derive(Debug, Clone)]
+pub struct Messages {
+    yes_expr: String,
+    no_expr: String,
+    yes_str: String,
+    no_str: String,
+    yes_regex: Option<ere::Regex>,
+    no_regex: Option<ere::Regex>,
+}
+
+impl Messages {
+    pub fn load_user_locale() -> Result<Messages> {
+        if let Ok(mut factory) = SystemLocaleFactory::new("") {
+            if let Some(messages) = factory.get_messages() {
+                return Ok(*messages);
+            }
+        }
+        Ok(Messages::english())
+    }
+
+    pub fn english() -> Messages {
+        Messages::new("^[yY]", "^[nN]", "yes", "no")
+    }
+
+    /// Constructs the messages information from POSIX extended regular expressions matching
+    /// affirmative and negative answers and the canonical words for them.
+    pub fn new(yes_expr: &str, no_expr: &str, yes_str: &str, no_str: &str) -> Messages {
+        Messages {
+            yes_expr: yes_expr.to_string(),
+            no_expr: no_expr.to_string(),
+            yes_str: yes_str.to_string(),
+            no_str: no_str.to_string(),
+            yes_regex: ere::Regex::new(yes_expr),
+            no_regex: ere::Regex::new(no_expr),
+        }
+    }
+
+    /// Returns the POSIX extended regular expression matching affirmative answers.
+    pub fn yes_expr(&self) -> &str {
+        &self.yes_expr
+    }
+
+    /// Returns the POSIX extended regular expression matching negative answers.
+    pub fn no_expr(&self) -> &str {
+        &self.no_expr
+    }
+
+    /// Returns the canonical affirmative answer, like "yes".
+    pub fn yes_str(&self) -> &str {
+        &self.yes_str
+    }
+
+    /// Returns the canonical negative answer, like "no".
+    pub fn no_str(&self) -> &str {
+        &self.no_str
+    }
+
+    /// Returns whether the response is an affirmative answer.
+    ///
+    /// Invalid expression matches nothing.
+    pub fn matches_yes(&self, response: &str) -> bool {
+        self.yes_regex.as_ref().filter(|re| re.is_match(response)).is_some()
+    }
+
+    /// Returns whether the response is a negative answer.
+    ///
+    /// Invalid expression matches nothing.
+    pub fn matches_no(&self, response: &str) -> bool {
+        self.no_regex.as_ref().filter(|re| re.is_match(response)).is_some()
+    }
+
+    /// Classifies the response to a yes/no question like C `rpmatch`: `Some(true)` for yes,
+    /// `Some(false)` for no and `None` if it is neither.
+    pub fn rpmatch(&self, response: &str) -> Option<bool> {
+        if self.matches_yes(response) {
+            Some(true)
+        } else if self.matches_no(response) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+// ---- collation stuff ----
+
+/// Comparison of strings in the order of some language, the implementation behind `Collator`.
+pub trait Collation: fmt::Debug {
+    /// Compares the strings.
+    fn compare(&self, a: &str, b: &str) -> Ordering;
+
+    /// Returns the sort key of the string. Comparing sort keys as byte strings gives the same
+    /// result as comparing the strings with `compare`.
+    fn sort_key(&self, s: &str) -> Vec<u8>;
+}
+
+/// Comparison in order of Unicode code points, which is what the C locale does.
+#[derive(Debug, Clone, Copy)]
+struct CodePointCollation;
+
+impl Collation for CodePointCollation {
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        a.cmp(b)
+    }
+
+    fn sort_key(&self, s: &str) -> Vec<u8> {
+        // UTF-8 is ordered by code points
+        s.as_bytes().to_vec()
+    }
+}
+
+/// Collation used when the system does not provide one.
+#[cfg(feature = "uca")]
+fn default_collation() -> Box<dyn Collation> {
+    Box::new(uca::UcaCollation::default())
+}
+
+/// Collation used when the system does not provide one.
+#[cfg(not(feature = "uca"))]
+fn default_collation() -> Box<dyn Collation> {
+    Box::new(CodePointCollation)
+}
+
+/// Information on how to order strings, for sorting lists shown to the user.
+#[derive(Debug)]
+pub struct Collator {
+    collation: Box<dyn Collation>,
+}
+
+impl Collator {
+    pub fn load_user_locale() -> Result<Collator> {
+        if let Ok(mut factory) = SystemLocaleFactory::new("") {
+            if let Some(collator) = factory.get_collator() {
+                return Ok(*collator);
+            }
+        }
+        Ok(Collator::new(default_collation()))
+    }
+
+    /// Returns collator ordering strings by code points, like the C locale.
+    pub fn invariant() -> Collator {
+        Collator::new(Box::new(CodePointCollation))
+    }
+
+    /// Constructs the collator with given implementation of the comparison.
+    pub fn new(collation: Box<dyn Collation>) -> Collator {
+        Collator { collation }
+    }
+
+    /// Compares the strings.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        self.collation.compare(a, b)
+    }
+
+    /// Returns the sort key of the string, for when a string is compared many times. The sort
+    /// keys compare as byte strings the same as the strings do with `compare`.
+    pub fn sort_key(&self, s: &str) -> Vec<u8> {
+        self.collation.sort_key(s)
+    }
+
+    /// Sorts the strings, using their sort keys.
+    pub fn sort<S: AsRef<str>>(&self, strings: &mut [S]) {
+        strings.sort_by_cached_key(|s| self.sort_key(s.as_ref()));
+    }
+}
+
+// ---- tests ----
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn thousands_separator() {
+        let numeric_options = Numeric::new("/", "=");
+        assert_eq!("1=234=567".to_string(), numeric_options.format_int(1234567))
+    }
+
+    #[test]
+    fn thousands_separator_2() {
+        let numeric_options = Numeric::new("/", "=");
+        assert_eq!("123=456".to_string(), numeric_options.format_int(123456))
+    }
+
+    #[test]
+    fn thousands_separator_3() {
+        let numeric_options = Numeric::new("/", "=");
+        assert_eq!("12=345=678".to_string(), numeric_options.format_int(12345678))
+    }
+
+    #[test]
+    fn indian_grouping() {
+        let numeric_options = Numeric::new_with_grouping(".", ",", &[3, 2]);
+        assert_eq!("12,34,56,789".to_string(), numeric_options.format_int(123456789))
+    }
+
+    #[test]
+    fn repeated_grouping() {
+        let numeric_options = Numeric::new_with_grouping(".", ",", &[3, 0]);
+        assert_eq!("123,456,789".to_string(), numeric_options.format_int(123456789));
+        let numeric_options = Numeric::new_with_grouping(".", ",", &[3, 2, 0, 1]);
+        assert_eq!("12,34,56,789".to_string(), numeric_options.format_int(123456789));
+        assert_eq!(Ok(123456789), numeric_options.parse_int("12,34,56,789"));
+        let numeric_options = Numeric::new_with_grouping(".", ",", &[0]);
+        assert_eq!("123456789".to_string(), numeric_options.format_int(123456789))
+    }
+
+    #[test]
+    fn no_further_grouping() {
+        let numeric_options = Numeric::new_with_grouping(".", ",", &[3, 127]);
+        assert_eq!("123456,789".to_string(), numeric_options.format_int(123456789))
+    }
+
+    #[test]
+    fn no_grouping() {
+        let numeric_options = Numeric::new_with_grouping(".", ",", &[]);
+        assert_eq!("123456789".to_string(), numeric_options.format_int(123456789));
+        let numeric_options = Numeric::new_with_grouping(".", ",", &[127]);
+        assert_eq!("123456789".to_string(), numeric_options.format_int(123456789))
+    }
+
+    #[test]
+    fn negative_int() {
+        let numeric_options = Numeric::new("/", "=");
+        assert_eq!("-123=456".to_string(), numeric_options.format_int(-123456));
+        assert_eq!("-1=234".to_string(), numeric_options.format_int(-1234i16));
+        assert_eq!("-123".to_string(), numeric_options.format_int(-123));
+        assert_eq!("0".to_string(), numeric_options.format_int(0));
+    }
+
+    #[test]
+    fn negative_sign() {
+        let mut numeric_options = Numeric::new(",", ".");
+        numeric_options.negative_sign = "\u{2212}".to_string();
+        assert_eq!("\u{2212}12.345".to_string(), numeric_options.format_int(-12345));
+    }
+
+    #[test]
+    fn int_boundaries() {
+        let numeric_options = Numeric::new(".", ",");
+        assert_eq!("-128".to_string(), numeric_options.format_int(i8::min_value()));
+        assert_eq!("255".to_string(), numeric_options.format_int(u8::max_value()));
+        assert_eq!("-9,223,372,036,854,775,808".to_string(),
+                   numeric_options.format_int(i64::min_value()));
+        assert_eq!("18,446,744,073,709,551,615".to_string(),
+                   numeric_options.format_int(u64::max_value()));
+        assert_eq!("-170,141,183,460,469,231,731,687,303,715,884,105,728".to_string(),
+                   numeric_options.format_int(i128::min_value()));
+        assert_eq!("340,282,366,920,938,463,463,374,607,431,768,211,455".to_string(),
+                   numeric_options.format_int(u128::max_value()));
+        assert_eq!("-1,000".to_string(), numeric_options.format_int(-1000isize));
+    }
+
+    #[test]
+    fn float_grouping() {
+        let numeric_options = Numeric::new(",", ".");
+        assert_eq!("1.234.567,89".to_string(), numeric_options.format_float(1234567.89, 2));
+        assert_eq!("-1.234.567,9".to_string(), numeric_options.format_float(-1234567.89, 1));
+        assert_eq!("1.234.568".to_string(), numeric_options.format_float(1234567.89f64, 0));
+        assert_eq!("0,50".to_string(), numeric_options.format_float(0.5f32, 2));
+        assert_eq!("-0,00".to_string(), numeric_options.format_float(-0.001, 2));
+    }
+
+    #[test]
+    fn float_separators() {
+        let numeric_options = Numeric::new(".", ",");
+        assert_eq!("1,234.5".to_string(), numeric_options.format_float(1234.5, 1));
+        let numeric_options = Numeric::new("\u{66b}", "\u{66c}");
+        assert_eq!("1\u{66c}234\u{66b}5".to_string(), numeric_options.format_float(1234.5, 1));
+        let numeric_options = Numeric::new_with_grouping(".", "\u{a0}", &[3, 2]);
+        assert_eq!("12\u{a0}34\u{a0}567.25".to_string(),
+                   numeric_options.format_float(1234567.25, 2));
+    }
+
+    #[test]
+    fn float_special() {
+        let mut numeric_options = Numeric::new(",", ".");
+        assert_eq!("nan".to_string(), numeric_options.format_float(::std::f64::NAN, 2));
+        assert_eq!("inf".to_string(), numeric_options.format_float(::std::f64::INFINITY, 2));
+        assert_eq!("-inf".to_string(), numeric_options.format_float(::std::f32::NEG_INFINITY, 2));
+        numeric_options.infinity = "\u{221e}".to_string();
+        assert_eq!("-\u{221e}".to_string(),
+                   numeric_options.format_float(::std::f64::NEG_INFINITY, 2));
+    }
+
+    #[test]
+    fn parse_int() {
+        let numeric_options = Numeric::new(",", ".");
+        assert_eq!(Ok(1234567), numeric_options.parse_int("1.234.567"));
+        assert_eq!(Ok(1234567), numeric_options.parse_int("1234567"));
+        assert_eq!(Ok(-1234), numeric_options.parse_int(" -1.234 "));
+        assert_eq!(Ok(12u8), numeric_options.parse_int("+12"));
+        assert_eq!(Ok(i64::min_value()), numeric_options.parse_int("-9.223.372.036.854.775.808"));
+        let numeric_options = Numeric::new_with_grouping(".", ",", &[3, 2]);
+        assert_eq!(Ok(123456789), numeric_options.parse_int("12,34,56,789"));
+    }
+
+    #[test]
+    fn parse_int_errors() {
+        fn err<T>(kind: ParseNumberErrorKind, position: usize)
+            -> ::std::result::Result<T, ParseNumberError>
+        {
+            Err(ParseNumberError::new(kind, position))
+        }
+
+        let numeric_options = Numeric::new(",", ".");
+        assert_eq!(err(ParseNumberErrorKind::InvalidGrouping, 2),
+                   numeric_options.parse_int::<i32>("12.34.567"));
+        assert_eq!(err(ParseNumberErrorKind::InvalidGrouping, 4),
+                   numeric_options.parse_int::<i32>("1234.567"));
+        assert_eq!(err(ParseNumberErrorKind::InvalidGrouping, 2),
+                   numeric_options.parse_int::<i32>("1..234"));
+        assert_eq!(err(ParseNumberErrorKind::InvalidGrouping, 5),
+                   numeric_options.parse_int::<i32>("1.234."));
+        assert_eq!(err(ParseNumberErrorKind::InvalidCharacter, 3),
+                   numeric_options.parse_int::<i32>("123x"));
+        assert_eq!(err(ParseNumberErrorKind::InvalidCharacter, 3),
+                   numeric_options.parse_int::<i32>("123,5"));
+        assert_eq!(err(ParseNumberErrorKind::Empty, 1),
+                   numeric_options.parse_int::<i32>(" - "));
+        assert_eq!(err(ParseNumberErrorKind::Empty, 3),
+                   numeric_options.parse_int::<i32>("   "));
+        assert_eq!(err(ParseNumberErrorKind::OutOfRange, 0),
+                   numeric_options.parse_int::<u8>("256"));
+        assert_eq!(err(ParseNumberErrorKind::OutOfRange, 0),
+                   numeric_options.parse_int::<u32>("-1"));
+        let numeric_options = Numeric::new_with_grouping(".", ",", &[3, 127]);
+        assert_eq!(err(ParseNumberErrorKind::InvalidGrouping, 3),
+                   numeric_options.parse_int::<i32>("123,456,789"));
+        assert_eq!(Ok(123456789), numeric_options.parse_int("123456,789"));
+    }
+
+    #[test]
+    fn parse_float() {
+        let numeric_options = Numeric::new(",", "\u{a0}");
+        assert_eq!(Ok(1234567.89), numeric_options.parse_float("1\u{a0}234\u{a0}567,89"));
+        assert_eq!(Ok(1234567.89), numeric_options.parse_float("1 234 567,89"));
+        assert_eq!(Ok(-0.5f32), numeric_options.parse_float("-0,5"));
+        assert_eq!(Ok(0.5), numeric_options.parse_float(",5"));
+        assert_eq!(Ok(12.0), numeric_options.parse_float("12,"));
+        assert_eq!(Ok(::std::f64::NEG_INFINITY), numeric_options.parse_float("-Infinity"));
+        assert!(numeric_options.parse_float::<f64>("nan").unwrap().is_nan());
+        assert_eq!(Err(ParseNumberError::new(ParseNumberErrorKind::Empty, 0)),
+                   numeric_options.parse_float::<f64>(","));
+        assert_eq!(Err(ParseNumberError::new(ParseNumberErrorKind::InvalidCharacter, 2)),
+                   numeric_options.parse_float::<f64>("12.5"));
+    }
+
+    #[test]
+    fn native_digits() {
+        let mut numeric_options = Numeric::new("\u{66b}", "\u{66c}");
+        numeric_options.native_digits = Some(
+            "\u{660}\u{661}\u{662}\u{663}\u{664}\u{665}\u{666}\u{667}\u{668}\u{669}"
+            .chars().map(|c| c.to_string()).collect());
+        assert_eq!("-\u{661}\u{66c}\u{662}\u{663}\u{664}",
+                   numeric_options.format_int_native(-1234));
+        assert_eq!("\u{661}\u{662}\u{66b}\u{665}\u{660}",
+                   numeric_options.format_float_native(12.5, 2));
+        assert_eq!("1\u{66c}234", numeric_options.format_int(1234));
+        assert_eq!(Ok(-1234), numeric_options.parse_int("-\u{661}\u{66c}\u{662}\u{663}\u{664}"));
+        assert_eq!(Ok(1234), numeric_options.parse_int("1\u{66c}234"));
+        assert_eq!(Ok(12.5), numeric_options.parse_float("\u{661}\u{662}\u{66b}\u{665}\u{660}"));
+    }
+
+    #[test]
+    fn input_digits() {
+        let mut numeric_options = Numeric::new(".", ",");
+        numeric_options.input_digits.push(
+            "\u{ff10}\u{ff11}\u{ff12}\u{ff13}\u{ff14}\u{ff15}\u{ff16}\u{ff17}\u{ff18}\u{ff19}"
+            .chars().map(|c| c.to_string()).collect());
+        assert_eq!(Ok(1905), numeric_options.parse_int("\u{ff11},\u{ff19}0\u{ff15}"));
+        assert_eq!("1,905", numeric_options.format_int_native(1905));
+    }
+
+    #[test]
+    fn monetary_format() {
+        let monetary = Monetary::english();
+        assert_eq!("$0.00", monetary.format(0));
+        assert_eq!("$0.05", monetary.format(5));
+        assert_eq!("$1,234,567.89", monetary.format(123456789));
+        assert_eq!("-$1.50", monetary.format(-150));
+        assert_eq!("USD 1.50", monetary.format_international(150));
+        assert_eq!("-USD 1,000.00", monetary.format_international(-100000));
+        assert_eq!("-$92,233,720,368,547,758.08", monetary.format(i64::min_value()));
+    }
+
+    #[test]
+    fn monetary_sign_positions() {
+        let mut monetary = Monetary::english();
+        monetary.local.symbol = "Kč".to_string();
+        monetary.local.frac_digits = 0;
+        let mut check = |cs_precedes, sep_by_space, sign_posn, expected| {
+            monetary.local.negative = SignFormat { cs_precedes, sep_by_space, sign_posn };
+            assert_eq!(expected, monetary.format(-5));
+        };
+        check(true, SymbolSpacing::NoSpace, SignPosition::Parentheses, "(Kč5)");
+        check(false, SymbolSpacing::SymbolSpace, SignPosition::Parentheses, "(5 Kč)");
+        check(true, SymbolSpacing::SignSpace, SignPosition::Before, "- Kč5");
+        check(false, SymbolSpacing::SignSpace, SignPosition::Before, "-5Kč");
+        check(true, SymbolSpacing::SymbolSpace, SignPosition::After, "Kč 5-");
+        check(false, SymbolSpacing::SignSpace, SignPosition::After, "5Kč -");
+        check(true, SymbolSpacing::SignSpace, SignPosition::BeforeSymbol, "- Kč5");
+        check(false, SymbolSpacing::SymbolSpace, SignPosition::BeforeSymbol, "5 -Kč");
+        check(false, SymbolSpacing::SignSpace, SignPosition::BeforeSymbol, "5- Kč");
+        check(true, SymbolSpacing::SymbolSpace, SignPosition::AfterSymbol, "Kč- 5");
+        check(true, SymbolSpacing::SignSpace, SignPosition::AfterSymbol, "Kč -5");
+        check(false, SymbolSpacing::SignSpace, SignPosition::AfterSymbol, "5Kč -");
+    }
+
+    #[test]
+    fn monetary_parse() {
+        let mut monetary = Monetary::english();
+        assert_eq!(Ok((-500, Some(CurrencySymbol::Local))), monetary.parse("-$5"));
+        assert_eq!(Ok((123456789, Some(CurrencySymbol::Local))), monetary.parse(" $1,234,567.89 "));
+        assert_eq!(Ok((150, None)), monetary.parse("1.5"));
+        assert_eq!(Ok((-150, Some(CurrencySymbol::International))), monetary.parse("usd -1.50"));
+        assert_eq!(Ok((-150, Some(CurrencySymbol::International))), monetary.parse("1.50- USD"));
+        assert_eq!(Ok((-1200, Some(CurrencySymbol::Local))), monetary.parse("($12.00)"));
+        monetary.local.symbol = "\u{20ac}".to_string();
+        assert_eq!(Ok((-1200, Some(CurrencySymbol::Local))), monetary.parse("(\u{20ac}12.00)"));
+
+        monetary.local.symbol = "K\u{10d}".to_string();
+        monetary.international.symbol = "CZK ".to_string();
+        monetary.decimal_sep = ",".to_string();
+        monetary.thousands_sep = "\u{202f}".to_string();
+        assert_eq!(Ok((123450, Some(CurrencySymbol::Local))), monetary.parse("1 234,50 K\u{10d}"));
+        assert_eq!(Ok((-123450, Some(CurrencySymbol::International))),
+                   monetary.parse("-1\u{202f}234,5 CZK"));
+    }
+
+    #[test]
+    fn monetary_parse_errors() {
+        let monetary = Monetary::english();
+        let err = |kind, position| Err(ParseNumberError::new(kind, position));
+        assert_eq!(err(ParseNumberErrorKind::FractionTooLong, 5), monetary.parse("$1.005"));
+        assert_eq!(err(ParseNumberErrorKind::InvalidGrouping, 3), monetary.parse("$12,34.00"));
+        assert_eq!(err(ParseNumberErrorKind::InvalidCharacter, 1), monetary.parse("--5"));
+        assert_eq!(err(ParseNumberErrorKind::InvalidCharacter, 3), monetary.parse("($5"));
+        assert_eq!(err(ParseNumberErrorKind::InvalidCharacter, 2), monetary.parse("$5$"));
+        assert_eq!(err(ParseNumberErrorKind::Empty, 1), monetary.parse("$"));
+        assert_eq!(err(ParseNumberErrorKind::Empty, 3), monetary.parse("   "));
+        assert_eq!(err(ParseNumberErrorKind::OutOfRange, 1),
+                   monetary.parse("$100,000,000,000,000,000"));
+    }
+
+    #[test]
+    fn monetary_dual() {
+        let mut monetary = Monetary::english();
+        assert!(monetary.at_date(Date::new(2000, 1, 1)).unwrap().dual.is_none());
+        assert_eq!(Date::new(2001, 12, 31), Date::from_yyyymmdd(20011231));
+
+        // Dutch guilder was replaced by euro, with a transition period where both were used
+        let mut euro = monetary.currency();
+        euro.local.symbol = "\u{20ac}".to_string();
+        euro.international.symbol = "EUR ".to_string();
+        euro.valid_from = Date::from_yyyymmdd(19990101);
+        monetary.local.symbol = "fl".to_string();
+        monetary.international.symbol = "NLG ".to_string();
+        monetary.valid_to = Date::from_yyyymmdd(20020128);
+        monetary.dual = Some(euro.clone());
+
+        assert_eq!("fl", monetary.currency_at(Date::new(1998, 6, 30)).unwrap().local.symbol);
+        assert_eq!("fl", monetary.currency_at(Date::new(2002, 1, 28)).unwrap().local.symbol);
+        assert_eq!(Some(euro), monetary.currency_at(Date::new(2002, 1, 29)));
+
+        let old = monetary.at_date(Date::new(1995, 1, 1)).unwrap();
+        assert_eq!("fl1,234.50", old.format(123450));
+        let new = monetary.at_date(Date::new(2010, 1, 1)).unwrap();
+        assert_eq!("\u{20ac}1,234.50", new.format(123450));
+        assert_eq!("EUR 1,234.50", new.format_international(123450));
+        assert_eq!(Ok((500, Some(CurrencySymbol::Local))), new.parse("\u{20ac}5"));
+        assert_eq!("NLG ", new.dual.unwrap().international.symbol);
+
+        monetary.dual.as_mut().unwrap().valid_to = Date::new(2099, 12, 31);
+        assert!(monetary.currency_at(Date::new(2100, 1, 1)).is_none());
+        assert!(monetary.at_date(Date::new(2100, 1, 1)).is_none());
+    }
+
+    #[test]
+    fn parse_round_trip() {
+        let options = vec![
+            Numeric::new(",", "."),
+            Numeric::new(".", ","),
+            Numeric::new_with_grouping(".", ",", &[3, 2]),
+            Numeric::new_with_grouping("\u{66b}", "\u{66c}", &[]),
+            Numeric::new(",", "\u{202f}"),
+        ];
+        for numeric_options in options {
+            for &n in &[0i64, 5, -12, 999, 1000, -123456, 1234567890,
+                        i64::max_value(), i64::min_value()] {
+                assert_eq!(Ok(n), numeric_options.parse_int(&numeric_options.format_int(n)));
+            }
+            for &f in &[0.0f64, -0.5, 1234.25, -9876543.125, 1e15] {
+                assert_eq!(Ok(f), numeric_options.parse_float(&numeric_options.format_float(f, 3)));
+            }
+            for &f in &[::std::f64::INFINITY, ::std::f64::NEG_INFINITY] {
+                assert_eq!(Ok(f), numeric_options.parse_float(&numeric_options.format_float(f, 3)));
+            }
+        }
+    }
+
+    #[test]
+    fn date_calculations() {
+        for days in -800000..800000 {
+            let date = Date::from_days_since_epoch(days);
+            assert_eq!(days, date.days_since_epoch(), "{:?}", date);
+        }
+        assert_eq!(Date::new(1970, 1, 1), Date::from_days_since_epoch(0));
+        assert_eq!(Date::new(1969, 12, 31), Date::from_days_since_epoch(-1));
+        assert_eq!(11017, Date::new(2000, 3, 1).days_since_epoch());
+        assert_eq!(2, Date::new(2024, 3, 5).day_of_week());
+        assert_eq!(0, Date::new(-5, 1, 1).day_of_week());
+        assert_eq!(60, Date::new(2023, 3, 1).day_of_year());
+        assert_eq!(61, Date::new(2024, 3, 1).day_of_year());
+        assert_eq!(366, Date::new(2000, 12, 31).day_of_year());
+        assert_eq!((2020, 53), Date::new(2021, 1, 3).iso_week());
+        assert_eq!((2021, 1), Date::new(2021, 1, 4).iso_week());
+        assert_eq!((2009, 1), Date::new(2008, 12, 29).iso_week());
+        assert_eq!((2024, 10), Date::new(2024, 3, 5).iso_week());
+        assert_eq!(MIN_TIMESTAMP, Date::new(i32::MIN, 1, 1).days_since_epoch() * 86400);
+        assert_eq!(MAX_TIMESTAMP + 1, (Date::new(i32::MAX, 12, 31).days_since_epoch() + 1) * 86400);
+        let last = DateTime::new(Date::new(i32::MAX, 12, 31), 23, 59, 59);
+        assert_eq!(DateTime { utc_offset: Some(3600), ..last },
+                   DateTime::from_timestamp(i64::MAX, 3600));
+        let first = DateTime::new(Date::new(i32::MIN, 1, 1), 0, 0, 0);
+        assert_eq!(DateTime { utc_offset: Some(-3600), ..first },
+                   DateTime::from_timestamp(i64::MIN, -3600));
+    }
+
+    #[test]
+    fn month_and_day_names() {
+        assert_eq!(Some(Month::January), Month::from_number(1));
+        assert_eq!(Some(Month::December), Month::from_number(12));
+        assert_eq!(None, Month::from_number(0));
+        assert_eq!(None, Month::from_number(13));
+        assert_eq!(9, Month::September.number());
+
+        let time = Time::english();
+        assert_eq!("February", time.long_month_name(Month::February));
+        assert_eq!("Feb", time.short_month_name(Month::February));
+        assert_eq!("February", time.standalone_long_month_name(Month::February));
+        assert_eq!("Wednesday", time.long_day_name(Weekday::Wednesday));
+        assert_eq!("Wed", time.short_day_name(Weekday::Wednesday));
+        let dt = DateTime::new(Date::new(2024, 13, 5), 17, 8, 9);
+        assert_eq!("? ? ?", time.format("%b %B %OB", &dt));
+        let dt = DateTime::new(Date::new(2024, 0, 5), 17, 8, 9);
+        assert_eq!("2024 ? ?", time.format("%Y %b %B", &dt));
+    }
+
+    #[test]
+    fn week_info() {
+        assert_eq!(Weekday::Tuesday, Date::new(2024, 3, 5).weekday());
+        assert_eq!(Weekday::Saturday, Weekday::Monday.add_days(-9));
+        assert_eq!(6, Weekday::from_days_from_sunday(-1).days_from_sunday());
+
+        let iso = WeekInfo {
+            first_weekday: Weekday::Monday,
+            min_days_in_first_week: 4,
+            first_workday: Weekday::Monday,
+        };
+        for days in -1000..1000 {
+            let date = Date::from_days_since_epoch(days * 13);
+            assert_eq!(date.iso_week(), iso.week_of_year(date), "{:?}", date);
+        }
+        let us = WeekInfo {
+            first_weekday: Weekday::Sunday,
+            min_days_in_first_week: 1,
+            first_workday: Weekday::Monday,
+        };
+        assert_eq!((2025, 1), us.week_of_year(Date::new(2024, 12, 29)));
+        assert_eq!((2024, 52), us.week_of_year(Date::new(2024, 12, 28)));
+        assert_eq!((2022, 1), us.week_of_year(Date::new(2022, 1, 1)));
+        assert_eq!((2022, 2), us.week_of_year(Date::new(2022, 1, 2)));
+        assert_eq!((2021, 52), iso.week_of_year(Date::new(2022, 1, 1)));
+
+        let egypt = WeekInfo {
+            first_weekday: Weekday::Saturday,
+            min_days_in_first_week: 1,
+            first_workday: Weekday::Sunday,
+        };
+        assert_eq!(vec![Weekday::Sunday, Weekday::Monday, Weekday::Tuesday, Weekday::Wednesday,
+                        Weekday::Thursday], egypt.workdays());
+        assert!(egypt.is_workday(Weekday::Thursday) && !egypt.is_workday(Weekday::Friday));
+        assert!(!us.is_workday(Weekday::Sunday) && us.is_workday(Weekday::Friday));
+    }
+
+    #[test]
+    fn month_calendar() {
+        let mut time = Time::english();
+        let calendar = time.month_calendar(2024, Month::March, None);
+        assert_eq!("March 2024", calendar.title);
+        assert_eq!(Weekday::Sunday, calendar.weekdays[0]);
+        assert_eq!("Sun", calendar.weekday_names[0]);
+        assert_eq!(6, calendar.weeks.len());
+        assert_eq!(vec![None, None, None, None, None, Some(1), Some(2)], calendar.weeks[0].days);
+        assert_eq!(Some(31), calendar.weeks[5].days[0]);
+        assert_eq!(concat!("     March 2024     \n",
+                           "Su Mo Tu We Th Fr Sa\n",
+                           "                1  2\n",
+                           " 3  4  5  6  7  8  9\n",
+                           "10 11 12 13 14 15 16\n",
+                           "17 18 19 20 21 22 23\n",
+                           "24 25 26 27 28 29 30\n",
+                           "31                  \n"),
+                   calendar.to_string());
+
+        time.week_info.first_weekday = Weekday::Monday;
+        let calendar = time.month_calendar(2021, Month::February, Some(WeekNumbers::Iso));
+        assert_eq!(4, calendar.weeks.len());
+        assert_eq!(vec![Some(5), Some(6), Some(7), Some(8)],
+                   calendar.weeks.iter().map(|week| week.number).collect::<Vec<_>>());
+        assert_eq!(concat!("     February 2021     \n",
+                           "   Mo Tu We Th Fr Sa Su\n",
+                           " 5  1  2  3  4  5  6  7\n",
+                           " 6  8  9 10 11 12 13 14\n",
+                           " 7 15 16 17 18 19 20 21\n",
+                           " 8 22 23 24 25 26 27 28\n",
+                           "                       \n",
+                           "                       \n"),
+                   calendar.to_string());
+
+        time.week_info.first_weekday = Weekday::Saturday;
+        time.calendar_direction = CalendarDirection::RightToLeft;
+        let calendar = time.month_calendar(2024, Month::March, Some(WeekNumbers::Locale));
+        assert_eq!(vec![Some(9), Some(10), Some(11), Some(12), Some(13), Some(14)],
+                   calendar.weeks.iter().map(|week| week.number).collect::<Vec<_>>());
+        assert_eq!(concat!("      March 2024       \n",
+                           "Fr Th We Tu Mo Su Sa   \n",
+                           " 1                    9\n",
+                           " 8  7  6  5  4  3  2 10\n",
+                           "15 14 13 12 11 10  9 11\n",
+                           "22 21 20 19 18 17 16 12\n",
+                           "29 28 27 26 25 24 23 13\n",
+                           "               31 30 14\n"),
+                   calendar.to_string());
+        time.calendar_direction = CalendarDirection::TopToBottom;
+        let calendar = time.month_calendar(2024, Month::March, None);
+        assert_eq!(concat!("     March 2024     \n",
+                           "Sa     2  9 16 23 30\n",
+                           "Su     3 10 17 24 31\n",
+                           "Mo     4 11 18 25   \n",
+                           "Tu     5 12 19 26   \n",
+                           "We     6 13 20 27   \n",
+                           "Th     7 14 21 28   \n",
+                           "Fr  1  8 15 22 29   \n"),
+                   calendar.to_string());
+    }
+
+    #[test]
+    fn time_format() {
+        let time = Time::english();
+        let mut dt = DateTime::new(Date::new(2024, 3, 5), 17, 8, 9);
+        assert_eq!("Tue Mar  5 17:08:09 2024", time.format("%c", &dt));
+        assert_eq!("Tuesday, 05 March 2024 05:08:09 PM", time.format("%A, %d %B %Y %r", &dt));
+        assert_eq!("2024-065 W10 W10 U09 2 2", time.format("%Y-%j W%W W%V U%U %u %w", &dt));
+        assert_eq!("20 24 24 2024 |5|  5| 5|05|", time.format("%C %y %g %G |%-d|%3e|%l|%I|", &dt));
+        assert_eq!("TUE pm PM MARCH", time.format("%^a %P %^p %#B", &dt));
+        assert_eq!("  2024-03-05 0003/05/24 %Ed %q %", time.format("%12F %010D %Ed %q %", &dt));
+        assert_eq!("\t1709658489\n", time.format("%t%s%n", &dt));
+        assert_eq!("", time.format("%z%Z", &dt));
+
+        dt.utc_offset = Some(-5400);
+        dt.zone = "NST".to_string();
+        assert_eq!("-0130 NST nst 1709663889", time.format("%z %Z %#Z %s", &dt));
+
+        dt.date = Date::new(-5, 1, 1);
+        assert_eq!("-5 -1 95 -000000005 |  -5|", time.format("%Y %C %y %010Y |%-4Y|", &dt));
+    }
+
+    #[test]
+    fn time_parse() {
+        let time = Time::english();
+        let mut dt = DateTime::new(Date::new(2024, 3, 5), 17, 8, 9);
+        assert_eq!(Ok(dt.clone()), time.parse("%c", "Tue Mar  5 17:08:09 2024"));
+        assert_eq!(Ok(dt.clone()),
+                   time.parse("%A, %d %B %Y %r", "tuesday, 5 MARCH 2024 5:08:09 pm"));
+        assert_eq!(Ok(dt.clone()), time.parse("%x%n%X", "03/05/24\t17:08:09"));
+        assert_eq!(Ok(dt.clone()), time.parse("%Y-%j %T", "2024-065 17:08:09"));
+        assert_eq!(Ok(dt.clone()), time.parse("%Y W%W %a %T", "2024 W10 Tue 17:08:09"));
+        assert_eq!(Ok(dt.clone()), time.parse("%Y U%U %w %T", "2024 U09 2 17:08:09"));
+        assert_eq!(Ok(dt.clone()), time.parse("%G-W%V-%u %T", "2024-W10-2 17:08:09"));
+        assert_eq!(Ok(dt.clone()), time.parse("%s", "1709658489"));
+        assert_eq!(Ok(dt.clone()), time.parse("%C%y%m%d%H%M%S", "20240305170809"));
+        assert_eq!(Ok(DateTime::new(Date::new(1970, 1, 1), 0, 0, 0)), time.parse("", "  "));
+        assert_eq!(Ok(Date::new(1969, 1, 1)), time.parse("%y", "69").map(|t| t.date));
+        assert_eq!(Ok(Date::new(2068, 1, 1)), time.parse("%y", "68").map(|t| t.date));
+        assert_eq!(Ok(Date::new(-5, 1, 1)), time.parse("%C %y", "-1 95").map(|t| t.date));
+        assert_eq!(Ok(Date::new(2021, 1, 3)), time.parse("%G-%V-%u", "2020-53-7").map(|t| t.date));
+
+        dt.utc_offset = Some(-5400);
+        dt.zone = "NST".to_string();
+        assert_eq!(Ok(dt.clone()), time.parse("%F %T %z %Z", "2024-03-05 17:08:09 -01:30 NST"));
+        dt.zone = String::new();
+        assert_eq!(Ok(dt.clone()), time.parse("%z %s", "-0130 1709663889"));
+        dt.utc_offset = Some(0);
+        assert_eq!(Ok(0), time.parse("%z", "Z").map(|t| t.utc_offset.unwrap()));
+    }
+
+    #[test]
+    fn eras() {
+        let reiwa = Era::parse("+:2:2020/01/01:+*:令和:%EC%Ey年").unwrap();
+        assert_eq!(EraDirection::Ascending, reiwa.direction);
+        assert_eq!(Date::new(2020, 1, 1), reiwa.start);
+        assert_eq!(EraEnd::EndOfTime, reiwa.end);
+        assert_eq!(("令和", "%EC%Ey年"), (&reiwa.name[..], &reiwa.format[..]));
+        assert!(reiwa.contains(Date::new(2020, 1, 1)) && !reiwa.contains(Date::new(2019, 12, 31)));
+        assert_eq!(6, reiwa.year_of(Date::new(2024, 3, 5)));
+        assert_eq!(2024, reiwa.gregorian_year(6));
+
+        let bc = Era::parse("+:1:-0001/12/31:-*:紀元前:%EC%Ey年").unwrap();
+        assert_eq!(Date::new(0, 12, 31), bc.start);
+        assert!(bc.contains(Date::new(-5, 1, 1)) && !bc.contains(Date::new(1, 1, 1)));
+        assert_eq!(6, bc.year_of(Date::new(-5, 1, 1)));
+        assert_eq!(-5, bc.gregorian_year(6));
+
+        let buddhist = Era::parse("+:1:-543/01/01:+*:พ.ศ.:%EC %Ey").unwrap();
+        assert_eq!(2567, buddhist.year_of(Date::new(2024, 3, 5)));
+        let countdown = Era::parse("-:10:2000/01/01:1990/01/01:BC:%Ey %EC").unwrap();
+        assert_eq!(5, countdown.year_of(Date::new(1995, 6, 1)));
+        assert!(countdown.contains(Date::new(1995, 6, 1)));
+        assert_eq!(None, Era::parse("*:1:2000/01/01:+*:X"));
+        assert_eq!(None, Era::parse("+:1:2000-01-01:+*:X:%Ey"));
+        assert_eq!(None, Era::parse("+:1:2000/01/01:+*:X:%_EY"));
+
+        let mut time = Time::english();
+        time.eras = vec![
+            reiwa,
+            Era::parse("+:1:2019/05/01:2019/12/31:令和:%EC元年").unwrap(),
+            Era::parse("+:2:1990/01/01:2019/04/30:平成:%EC%Ey年").unwrap(),
+            bc,
+        ];
+        let mut dt = DateTime::new(Date::new(2024, 3, 5), 17, 8, 9);
+        assert_eq!("令和06年 令和 06 |6|", time.format("%EY %EC %Ey |%-Ey|", &dt));
+        assert_eq!("令和 6年 Tue Mar  5 17:08:09 2024", time.format("%_EY %Ec", &dt));
+        assert_eq!(Ok(dt.date), time.parse("%EY %m %d", "令和06年 3 5").map(|t| t.date));
+        assert_eq!(Ok(dt.date), time.parse("%Ey %m %d", "6 3 5").map(|t| t.date));
+        dt.date = Date::new(2019, 5, 1);
+        assert_eq!("令和元年", time.format("%EY", &dt));
+        assert_eq!(Ok(dt.date), time.parse("%EY %m %d", "令和元年 5 1").map(|t| t.date));
+        dt.date = Date::new(2019, 4, 30);
+        assert_eq!("平成31年", time.format("%EY", &dt));
+        assert_eq!(Ok(dt.date), time.parse("%Ey %EC %m %d", "31 平成 4 30").map(|t| t.date));
+        dt.date = Date::new(1800, 1, 1);
+        assert_eq!("1800 18 00", time.format("%EY %EC %Ey", &dt));
+    }
+
+    #[test]
+    fn alt_digits() {
+        let mut time = Time::english();
+        time.alt_digits = ["zero", "one", "two", "three", "four", "five", "", "seven"].iter()
+            .map(|s| s.to_string())
+            .collect();
+        let dt = DateTime::new(Date::new(2024, 3, 5), 17, 7, 6);
+        assert_eq!("three/five/24 17:seven:06", time.format("%Om/%Oe/%Oy %OH:%OM:%OS", &dt));
+        assert_eq!("  five|00two|1709658426", time.format("%6Od|%05Ou|%Os", &dt));
+        assert_eq!(Ok(dt.clone()),
+                   time.parse("%Om/%Oe/%Oy %OH:%OM:%OS", "three/five/24 17:seven:6"));
+        assert_eq!(Ok(dt.clone()), time.parse("%Om/%Od/%y %T", "3/FIVE/24 17:07:06"));
+        assert_eq!(Err(ParseTimeError::new(ParseTimeErrorKind::OutOfRange, 0)),
+                   time.parse("%Om", "zero"));
+    }
+
+    #[test]
+    fn time_parse_errors() {
+        let time = Time::english();
+        let err = |kind, position| Err(ParseTimeError::new(kind, position));
+        assert_eq!(err(ParseTimeErrorKind::Mismatch, 4), time.parse("%b %d", "Mar x"));
+        assert_eq!(err(ParseTimeErrorKind::Mismatch, 0), time.parse("%b %d", "Mrz 5"));
+        assert_eq!(err(ParseTimeErrorKind::Mismatch, 2), time.parse("%H:%M", "17"));
+        assert_eq!(err(ParseTimeErrorKind::OutOfRange, 3), time.parse("%H:%M", "17:60"));
+        assert_eq!(err(ParseTimeErrorKind::OutOfRange, 0), time.parse("%m", "13"));
+        assert_eq!(err(ParseTimeErrorKind::TrailingCharacters, 5), time.parse("%H:%M", "17:08:09"));
+        assert_eq!(err(ParseTimeErrorKind::InvalidDate, 10), time.parse("%F", "2023-02-29"));
+        assert_eq!(err(ParseTimeErrorKind::InvalidDate, 8), time.parse("%Y %j", "2023 366"));
+        assert_eq!(err(ParseTimeErrorKind::InvalidPattern, 0), time.parse("%q", "1"));
+        assert_eq!(err(ParseTimeErrorKind::Mismatch, 0), time.parse("%z", "0100"));
+        assert_eq!(err(ParseTimeErrorKind::OutOfRange, 25),
+                   time.parse("%s %z", "9223372036854775000 +1400"));
+        assert_eq!(err(ParseTimeErrorKind::OutOfRange, 19),
+                   time.parse("%s", "9223372036854775807"));
+        assert_eq!(err(ParseTimeErrorKind::OutOfRange, 17),
+                   time.parse("%s", "67767976233532800"));
+        assert_eq!(i32::MAX, time.parse("%s", "67767976233532799").unwrap().date.year);
+    }
+
+    #[test]
+    fn duration_format() {
+        let rt = RelativeTime::english();
+        let duration = ::std::time::Duration::from_secs(3 * 3600 + 5 * 60);
+        assert_eq!("3 hours 5 minutes", rt.format_duration(duration, UnitStyle::Long));
+        assert_eq!("3 hr 5 min", rt.format_duration(duration, UnitStyle::Short));
+        assert_eq!("3h 5m", rt.format_duration(duration, UnitStyle::Narrow));
+        let duration = ::std::time::Duration::from_millis(86400_999 + 1000);
+        assert_eq!("1 day 1 second", rt.format_duration(duration, UnitStyle::Long));
+        assert_eq!("0 seconds", rt.format_duration(Default::default(), UnitStyle::Long));
+        assert_eq!("2 wks", rt.format_amount(2, TimeUnit::Week, UnitStyle::Short));
+    }
+
+    #[test]
+    fn relative_time_format() {
+        let rt = RelativeTime::english();
+        assert_eq!("2 days ago", rt.format_relative(-2 * 86400 - 7200, UnitStyle::Long));
+        assert_eq!("in 5 minutes", rt.format_relative(5 * 60 + 59, UnitStyle::Long));
+        assert_eq!("in 1 minute", rt.format_relative(60, UnitStyle::Long));
+        assert_eq!("1 yr ago", rt.format_relative(-400 * 86400, UnitStyle::Short));
+        assert_eq!("in 3mo", rt.format_relative(95 * 86400, UnitStyle::Narrow));
+        assert_eq!("now", rt.format_relative(0, UnitStyle::Narrow));
+        assert_eq!("1 week ago", rt.format_relative_amount(-1, TimeUnit::Week, UnitStyle::Long));
+    }
+
+    #[test]
+    fn relative_time_custom() {
+        let patterns = |forms: &[&str]| forms.iter().map(|s| s.to_string()).collect();
+        let mut units = HashMap::new();
+        units.insert((TimeUnit::Minute, UnitStyle::Long), UnitPatterns {
+            duration: patterns(&["{0} minuta", "{0} minuty", "{0} minut"]),
+            past: patterns(&["{0} minutę temu", "{0} minuty temu", "{0} minut temu"]),
+            future: patterns(&["za {0} minutę", "za {0} minuty", "za {0} minut"]),
+        });
+        let rt = RelativeTime {
+            units,
+            plural_rule: PluralRule::parse("nplurals=3; plural=(n==1 ? 0 : n%10>=2 && n%10<=4 \
+                                            && (n%100<10 || n%100>=20) ? 1 : 2);").unwrap(),
+            now: "teraz".to_string(),
+            separator: ", ".to_string(),
+        };
+        assert_eq!("za 22 minuty", rt.format_relative(22 * 60, UnitStyle::Narrow));
+        assert_eq!("1 minutę temu", rt.format_relative(-60, UnitStyle::Long));
+        assert_eq!("12 minut", rt.format_amount(12, TimeUnit::Minute, UnitStyle::Short));
+        assert_eq!("5 minut, 7", rt.format_duration(::std::time::Duration::from_secs(307),
+                                                    UnitStyle::Long));
+    }
+
+    #[test]
+    fn messages() {
+        let messages = Messages::english();
+        assert!(messages.matches_yes("yes") && messages.matches_yes("Y"));
+        assert!(!messages.matches_yes("no") && !messages.matches_yes(" y"));
+        assert!(messages.matches_no("Nope"));
+        assert_eq!(Some(true), messages.rpmatch(messages.yes_str()));
+        assert_eq!(Some(false), messages.rpmatch(messages.no_str()));
+        assert_eq!(None, messages.rpmatch("maybe"));
+
+        let messages = Messages::new("^([+1aAyY]|ano)", "^[-0nN]", "ano", "ne");
+        assert_eq!(Some(true), messages.rpmatch("ano"));
+        assert_eq!(Some(false), messages.rpmatch("ne"));
+        let messages = Messages::new("^([yY]", "^[nN]", "yes", "no");
+        assert_eq!(None, messages.rpmatch("yes"));
+        let nested = format!("{}y{}", "(".repeat(1000), ")".repeat(1000));
+        assert!(!Messages::new(&nested, "^[nN]", "yes", "no").matches_yes("y"));
+    }
+
+    #[test]
+    fn invariant_collation() {
+        let collator = Collator::invariant();
+        let mut words = vec!["b", "ä", "B", "a", "ab", ""];
+        collator.sort(&mut words);
+        assert_eq!(vec!["", "B", "a", "ab", "b", "ä"], words);
+        assert_eq!(Ordering::Less, collator.compare("a", "ab"));
+        assert_eq!(Ordering::Equal, collator.compare("ä", "ä"));
+    }
+
+    #[test]
+    fn custom_collation() {
+        #[derive(Debug)]
+        struct IgnoreCase;
+
+        impl Collation for IgnoreCase {
+            fn compare(&self, a: &str, b: &str) -> Ordering {
+                a.to_lowercase().cmp(&b.to_lowercase())
+            }
+
+            fn sort_key(&self, s: &str) -> Vec<u8> {
+                s.to_lowercase().into_bytes()
+            }
+        }
+
+        let collator = Collator::new(Box::new(IgnoreCase));
+        let mut words = vec!["b", "C", "a", "B"];
+        collator.sort(&mut words);
+        assert_eq!(vec!["a", "b", "B", "C"], words);
+        assert_eq!(Ordering::Equal, collator.compare("ABC", "abc"));
     }
 }
@@ -211,7 +211,55 @@ pub const _NL_TIME_TIMEZONE: ::libc::c_uint = 131179;
 pub const _DATE_FMT: ::libc::c_uint = 131180;
 pub const _NL_W_DATE_FMT: ::libc::c_uint = 131181;
 pub const _NL_TIME_CODESET: ::libc::c_uint = 131182;
-pub const _NL_NUM_LC_TIME: ::libc::c_uint = 131183;
+pub const __ALTMON_1: ::libc::c_uint = 131183;
+pub const __ALTMON_2: ::libc::c_uint = 131184;
+pub const __ALTMON_3: ::libc::c_uint = 131185;
+pub const __ALTMON_4: ::libc::c_uint = 131186;
+pub const __ALTMON_5: ::libc::c_uint = 131187;
+pub const __ALTMON_6: ::libc::c_uint = 131188;
+pub const __ALTMON_7: ::libc::c_uint = 131189;
+pub const __ALTMON_8: ::libc::c_uint = 131190;
+pub const __ALTMON_9: ::libc::c_uint = 131191;
+pub const __ALTMON_10: ::libc::c_uint = 131192;
+pub const __ALTMON_11: ::libc::c_uint = 131193;
+pub const __ALTMON_12: ::libc::c_uint = 131194;
+pub const _NL_WALTMON_1: ::libc::c_uint = 131195;
+pub const _NL_WALTMON_2: ::libc::c_uint = 131196;
+pub const _NL_WALTMON_3: ::libc::c_uint = 131197;
+pub const _NL_WALTMON_4: ::libc::c_uint = 131198;
+pub const _NL_WALTMON_5: ::libc::c_uint = 131199;
+pub const _NL_WALTMON_6: ::libc::c_uint = 131200;
+pub const _NL_WALTMON_7: ::libc::c_uint = 131201;
+pub const _NL_WALTMON_8: ::libc::c_uint = 131202;
+pub const _NL_WALTMON_9: ::libc::c_uint = 131203;
+pub const _NL_WALTMON_10: ::libc::c_uint = 131204;
+pub const _NL_WALTMON_11: ::libc::c_uint = 131205;
+pub const _NL_WALTMON_12: ::libc::c_uint = 131206;
+pub const _NL_ABALTMON_1: ::libc::c_uint = 131207;
+pub const _NL_ABALTMON_2: ::libc::c_uint = 131208;
+pub const _NL_ABALTMON_3: ::libc::c_uint = 131209;
+pub const _NL_ABALTMON_4: ::libc::c_uint = 131210;
+pub const _NL_ABALTMON_5: ::libc::c_uint = 131211;
+pub const _NL_ABALTMON_6: ::libc::c_uint = 131212;
+pub const _NL_ABALTMON_7: ::libc::c_uint = 131213;
+pub const _NL_ABALTMON_8: ::libc::c_uint = 131214;
+pub const _NL_ABALTMON_9: ::libc::c_uint = 131215;
+pub const _NL_ABALTMON_10: ::libc::c_uint = 131216;
+pub const _NL_ABALTMON_11: ::libc::c_uint = 131217;
+pub const _NL_ABALTMON_12: ::libc::c_uint = 131218;
+pub const _NL_WABALTMON_1: ::libc::c_uint = 131219;
+pub const _NL_WABALTMON_2: ::libc::c_uint = 131220;
+pub const _NL_WABALTMON_3: ::libc::c_uint = 131221;
+pub const _NL_WABALTMON_4: ::libc::c_uint = 131222;
+pub const _NL_WABALTMON_5: ::libc::c_uint = 131223;
+pub const _NL_WABALTMON_6: ::libc::c_uint = 131224;
+pub const _NL_WABALTMON_7: ::libc::c_uint = 131225;
+pub const _NL_WABALTMON_8: ::libc::c_uint = 131226;
+pub const _NL_WABALTMON_9: ::libc::c_uint = 131227;
+pub const _NL_WABALTMON_10: ::libc::c_uint = 131228;
+pub const _NL_WABALTMON_11: ::libc::c_uint = 131229;
+pub const _NL_WABALTMON_12: ::libc::c_uint = 131230;
+pub const _NL_NUM_LC_TIME: ::libc::c_uint = 131231;
 pub const _NL_COLLATE_NRULES: ::libc::c_uint = 196608;
 pub const _NL_COLLATE_RULESETS: ::libc::c_uint = 196609;
 pub const _NL_COLLATE_TABLEMB: ::libc::c_uint = 196610;
@@ -464,4 +512,13 @@ extern "C" {
                  __outbuf: *mut *mut ::libc::c_char,
                  __outbytesleft: *mut size_t) -> size_t;
     pub fn iconv_close(__cd: iconv_t) -> ::libc::c_int;
+    pub fn strfmon_l(__s: *mut ::libc::c_char, __maxsize: size_t, __loc: __locale_t,
+                     __format: *const ::libc::c_char, ...) -> ::libc::ssize_t;
+    pub fn strftime_l(__s: *mut ::libc::c_char, __maxsize: size_t,
+                      __format: *const ::libc::c_char, __tp: *const ::libc::tm,
+                      __loc: __locale_t) -> size_t;
+    pub fn strcoll_l(__s1: *const ::libc::c_char, __s2: *const ::libc::c_char,
+                     __l: __locale_t) -> ::libc::c_int;
+    pub fn strxfrm_l(__dest: *mut ::libc::c_char, __src: *const ::libc::c_char, __n: size_t,
+                     __l: __locale_t) -> size_t;
 }
@@ -15,7 +15,7 @@ pub trait LanginfoItem<'a> : Copy + Sized {
     fn to_ffi(self) -> ffi::nl_item;
 }
 
-unsafe fn decode_string<'a>(ptr: *const ::libc::c_char, iconv: Option<&IConv>) -> Cow<'a, str> {
+pub(super) unsafe fn decode_string<'a>(ptr: *const ::libc::c_char, iconv: Option<&IConv>) -> Cow<'a, str> {
     if ptr.is_null() {
         return Cow::Borrowed("");
     }
@@ -33,13 +33,14 @@ unsafe fn decode_string<'a>(ptr: *const ::libc::c_char, iconv: Option<&IConv>) -
     return String::from_utf8_lossy(cres.to_bytes());
 }
 
-unsafe fn decode_strings<'a>(mut ptr: *const ::libc::c_char, iconv: Option<&IConv>, max: usize) -> Vec<Cow<'a, str>> {
+unsafe fn decode_strings<'a>(mut ptr: *const ::libc::c_char, iconv: Option<&IConv>, mut max: usize) -> Vec<Cow<'a, str>> {
     let mut res = Vec::with_capacity(max);
     while max > 0 && !ptr.is_null() && *ptr != 0 {
         let len = CStr::from_ptr(ptr).to_bytes_with_nul().len();
         let s = decode_string(ptr, iconv);
         ptr = ptr.offset(len as isize);
         res.push(s);
+        max -= 1;
     }
     return res;
 }
@@ -251,6 +252,30 @@ pub enum TimeStringItems {
     _NL_TIME_ERA_ENTRIES = ffi::_NL_TIME_ERA_ENTRIES as isize,
     _NL_TIME_TIMEZONE = ffi::_NL_TIME_TIMEZONE as isize,
     _DATE_FMT = ffi::_DATE_FMT as isize,
+    ALTMON_1 = ffi::__ALTMON_1 as isize,
+    ALTMON_2 = ffi::__ALTMON_2 as isize,
+    ALTMON_3 = ffi::__ALTMON_3 as isize,
+    ALTMON_4 = ffi::__ALTMON_4 as isize,
+    ALTMON_5 = ffi::__ALTMON_5 as isize,
+    ALTMON_6 = ffi::__ALTMON_6 as isize,
+    ALTMON_7 = ffi::__ALTMON_7 as isize,
+    ALTMON_8 = ffi::__ALTMON_8 as isize,
+    ALTMON_9 = ffi::__ALTMON_9 as isize,
+    ALTMON_10 = ffi::__ALTMON_10 as isize,
+    ALTMON_11 = ffi::__ALTMON_11 as isize,
+    ALTMON_12 = ffi::__ALTMON_12 as isize,
+    _NL_ABALTMON_1 = ffi::_NL_ABALTMON_1 as isize,
+    _NL_ABALTMON_2 = ffi::_NL_ABALTMON_2 as isize,
+    _NL_ABALTMON_3 = ffi::_NL_ABALTMON_3 as isize,
+    _NL_ABALTMON_4 = ffi::_NL_ABALTMON_4 as isize,
+    _NL_ABALTMON_5 = ffi::_NL_ABALTMON_5 as isize,
+    _NL_ABALTMON_6 = ffi::_NL_ABALTMON_6 as isize,
+    _NL_ABALTMON_7 = ffi::_NL_ABALTMON_7 as isize,
+    _NL_ABALTMON_8 = ffi::_NL_ABALTMON_8 as isize,
+    _NL_ABALTMON_9 = ffi::_NL_ABALTMON_9 as isize,
+    _NL_ABALTMON_10 = ffi::_NL_ABALTMON_10 as isize,
+    _NL_ABALTMON_11 = ffi::_NL_ABALTMON_11 as isize,
+    _NL_ABALTMON_12 = ffi::_NL_ABALTMON_12 as isize,
 }
 
 impl<'a> LanginfoItem<'a> for TimeStringItems {
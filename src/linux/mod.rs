@@ -1,10 +1,12 @@
 //! Locale implementation using GNU libc
 
 use ::std::borrow::Cow;
+use ::std::cmp::Ordering;
 use ::std::ffi::{CStr,CString};
-use ::std::io::{Error,Result};
+use ::std::io::{Error,ErrorKind,Result};
 use ::std::sync::Arc;
-use super::{LocaleFactory,Numeric,Time};
+use super::{Currency,CurrencyFormat,Date,Era,LocaleFactory,Monetary,Numeric,SignFormat,Time};
+use super::{CalendarDirection,Collation,Collator,Messages,WeekInfo};
 
 pub mod ffi;
 pub mod langinfo;
@@ -147,6 +149,207 @@ impl Drop for IConv {
     }
 }
 
+/// Returns converter from charset of the codeset item of `locale` to UTF-8, unless it is UTF-8.
+fn get_iconv(codeset: langinfo::CodesetItems, locale: &CLocale) -> Option<Arc<IConv>> {
+    let cs = unsafe {
+        ::std::str::from_utf8_unchecked(
+            ::std::ffi::CStr::from_ptr(
+                ffi::nl_langinfo_l(codeset as ::libc::c_uint, locale.c_locale)).to_bytes())
+    };
+    if cs != "UTF-8" {
+        if let Ok(i) = IConv::new("UTF-8", cs) {
+            return Some(Arc::new(i));
+        }
+    }
+    return None;
+}
+
+/// Catalog locations searched by GNU libc after those in `NLSPATH`.
+const DEFAULT_NLSPATH: &str = concat!(
+    "/usr/share/locale/%L/%N:/usr/share/locale/%L/LC_MESSAGES/%N:",
+    "/usr/share/locale/%l/%N:/usr/share/locale/%l/LC_MESSAGES/%N");
+
+/// X/Open message catalog.
+///
+/// See [`catopen`(3)](http://man7.org/linux/man-pages/man3/catopen.3.html). The messages are
+/// identified by set and message numbers and are converted to UTF-8 from the charset of
+/// `LC_MESSAGES` of the locale.
+#[derive(Debug)]
+pub struct MessageCatalog {
+    catd: ffi::nl_catd,
+    iconv: Option<Arc<IConv>>,
+}
+
+impl MessageCatalog {
+    /// Opens catalog `name` for `LC_MESSAGES` of `locale`.
+    ///
+    /// If `name` contains `/`, it is the path of the catalog. Otherwise the catalog is searched
+    /// for in the locations given by `NLSPATH` and then in the default ones, like `catopen` does
+    /// with `NL_CAT_LOCALE`. Unlike `catopen`, this uses the given locale rather than the global
+    /// one, which Rust programs don't usually set. Returns error of kind `NotFound` if there is no
+    /// such catalog.
+    pub fn open(name: &str, locale: &CLocale) -> Result<MessageCatalog> {
+        let nlspath = match ::std::env::var("NLSPATH") {
+            Ok(ref nlspath) if !nlspath.is_empty() => format!("{}:{}", nlspath, DEFAULT_NLSPATH),
+            _ => DEFAULT_NLSPATH.to_string(),
+        };
+        MessageCatalog::open_in(name, locale, &nlspath)
+    }
+
+    fn open_in(name: &str, locale: &CLocale, nlspath: &str) -> Result<MessageCatalog> {
+        for path in catalog_paths(name, &locale.name(ffi::LC_MESSAGES), nlspath) {
+            // a path without slash would make catopen search the catalog itself
+            let path = if path.contains('/') { path } else { format!("./{}", path) };
+            let cpath = CString::new(path)?;
+            let catd = unsafe { ffi::catopen(cpath.as_ptr(), 0) };
+            if catd as isize != -1 {
+                return Ok(MessageCatalog {
+                    catd,
+                    iconv: get_iconv(langinfo::_NL_MESSAGES_CODESET, locale),
+                });
+            }
+        }
+        Err(Error::new(ErrorKind::NotFound, format!("message catalog {} not found", name)))
+    }
+
+    /// Returns message `number` from set `set`, or `default` if the catalog does not contain it.
+    ///
+    /// See [`catgets`(3)](http://man7.org/linux/man-pages/man3/catgets.3.html).
+    pub fn get<'a>(&self, set: i32, number: i32, default: &'a str) -> Cow<'a, str> {
+        unsafe {
+            let ptr = ffi::catgets(self.catd, set, number, ::std::ptr::null());
+            if ptr.is_null() {
+                Cow::Borrowed(default)
+            } else {
+                Cow::Owned(langinfo::decode_string(ptr, self.iconv.as_deref()).into_owned())
+            }
+        }
+    }
+}
+
+impl Drop for MessageCatalog {
+    fn drop(&mut self) {
+        unsafe { ffi::catclose(self.catd); }
+    }
+}
+
+/// Returns the paths to try for catalog `name` and `locale` in order, from the colon-separated
+/// templates of `nlspath`.
+///
+/// The templates can contain `%N` for the name, `%L` for the locale, `%l`, `%t` and `%c` for its
+/// language, territory and codeset and `%%` for `%`. Empty template stands for just the name.
+fn catalog_paths(name: &str, locale: &str, nlspath: &str) -> Vec<String> {
+    if name.contains('/') {
+        return vec![name.to_string()];
+    }
+    let without_modifier = locale.split('@').next().unwrap_or("");
+    let mut parts = without_modifier.splitn(2, '.');
+    let language_territory = parts.next().unwrap_or("");
+    let codeset = parts.next().unwrap_or("");
+    let mut parts = language_territory.splitn(2, '_');
+    let language = parts.next().unwrap_or("");
+    let territory = parts.next().unwrap_or("");
+    nlspath.split(':')
+        .map(|template| {
+            if template.is_empty() {
+                return name.to_string();
+            }
+            let mut path = String::new();
+            let mut chars = template.chars();
+            while let Some(c) = chars.next() {
+                if c != '%' {
+                    path.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some('N') => path += name,
+                    Some('L') => path += locale,
+                    Some('l') => path += language,
+                    Some('t') => path += territory,
+                    Some('c') => path += codeset,
+                    Some('%') => path.push('%'),
+                    Some(other) => {
+                        path.push('%');
+                        path.push(other);
+                    }
+                    None => path.push('%'),
+                }
+            }
+            path
+        })
+        .collect()
+}
+
+/// Collation of `LC_COLLATE` by `strcoll_l` and `strxfrm_l`.
+#[derive(Debug)]
+struct LibCCollation {
+    locale: Arc<CLocale>,
+    /// Converter from UTF-8 to charset of the collation, unless it can handle UTF-8 directly.
+    iconv: Option<IConv>,
+}
+
+impl LibCCollation {
+    fn new(locale: Arc<CLocale>) -> LibCCollation {
+        let codeset = unsafe {
+            CStr::from_ptr(ffi::nl_langinfo_l(ffi::_NL_COLLATE_CODESET, locale.c_locale))
+        }.to_string_lossy().into_owned();
+        // the C locale compares bytes, which keeps UTF-8 in order of code points
+        let iconv = if codeset == "UTF-8" || codeset == "ANSI_X3.4-1968" {
+            None
+        } else {
+            // characters missing from the charset are approximated rather than dropped
+            IConv::new(&format!("{}//TRANSLIT", codeset), "UTF-8").ok()
+        };
+        LibCCollation { locale, iconv }
+    }
+
+    /// Converts the string to the collation charset. Like in C, the string ends at the first NUL.
+    /// Characters that can't be converted are replaced with `?`.
+    fn to_c_string(&self, s: &str) -> CString {
+        let s = s.split('\0').next().unwrap_or("");
+        let bytes = match self.iconv {
+            Some(ref iconv) => {
+                let mut bytes = Vec::with_capacity(s.len());
+                let mut buf = [0u8; 256];
+                let mut rest = s;
+                while !rest.is_empty() {
+                    let (res, read, written) = iconv.convert(rest.as_bytes(), &mut buf);
+                    let error = ::std::io::Error::last_os_error().raw_os_error();
+                    bytes.extend_from_slice(&buf[..written]);
+                    rest = &rest[read..];
+                    if res < 0 && error != Some(::libc::E2BIG) {
+                        bytes.push(b'?');
+                        let skip = rest.chars().next().map_or(rest.len(), char::len_utf8);
+                        rest = &rest[skip..];
+                    }
+                }
+                bytes
+            }
+            None => s.as_bytes().to_vec(),
+        };
+        CString::new(bytes).unwrap_or_default()
+    }
+}
+
+impl Collation for LibCCollation {
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        let (a, b) = (self.to_c_string(a), self.to_c_string(b));
+        unsafe { ffi::strcoll_l(a.as_ptr(), b.as_ptr(), self.locale.c_locale) }.cmp(&0)
+    }
+
+    fn sort_key(&self, s: &str) -> Vec<u8> {
+        let s = self.to_c_string(s);
+        unsafe {
+            let len = ffi::strxfrm_l(::std::ptr::null_mut(), s.as_ptr(), 0, self.locale.c_locale);
+            let mut key = vec![0u8; len + 1];
+            ffi::strxfrm_l(key.as_mut_ptr() as *mut ::libc::c_char, s.as_ptr(), key.len(),
+                           self.locale.c_locale);
+            key.truncate(len);
+            key
+        }
+    }
+}
+
 // FIXME FIXME FIXME #[derive(Clone)]
 #[derive(Debug)]
 pub struct LibCLocaleFactory {
@@ -175,19 +378,6 @@ impl LibCLocaleFactory {
     // TODO TODO: Could also try overriding all components to their corresponding UTF-8 variants,
     // though that's quite a bit more work.
     pub fn new_from_c_locale(c_locale: CLocale) -> Self {
-        fn get_iconv(codeset: langinfo::CodesetItems, locale: &CLocale) -> Option<Arc<IConv>> {
-            let cs = unsafe {
-                ::std::str::from_utf8_unchecked(
-                    ::std::ffi::CStr::from_ptr(
-                        ffi::nl_langinfo_l(codeset as ::libc::c_uint, locale.c_locale)).to_bytes())
-            };
-            if cs != "UTF-8" {
-                if let Ok(i) = IConv::new("UTF-8", cs) {
-                    return Some(Arc::new(i));
-                }
-            }
-            return None;
-        }
         return LibCLocaleFactory{
             iconv: [
                 get_iconv(langinfo::_NL_COLLATE_CODESET, &c_locale),
@@ -213,6 +403,13 @@ impl LibCLocaleFactory {
         return Ok(LibCLocaleFactory::new_from_c_locale(loc));
     }
 
+    /// Opens message catalog `name` for `LC_MESSAGES` of the locale.
+    ///
+    /// See `MessageCatalog::open`.
+    pub fn message_catalog(&self, name: &str) -> Result<MessageCatalog> {
+        MessageCatalog::open(name, &self.locale)
+    }
+
     pub fn langinfo<'a, I>(&'a self, item: I) -> I::Type
         where I: langinfo::LanginfoItem<'a>
     {
@@ -226,15 +423,159 @@ impl LibCLocaleFactory {
             item.decode(ffi::nl_langinfo_l(item.to_ffi(), self.locale.c_locale), conv)
         }
     }
+
+    /// Returns output digits of `LC_CTYPE`, unless they are the ASCII ones.
+    fn native_digits(&self) -> Option<Vec<String>> {
+        let digits: Vec<String> = [
+            langinfo::_NL_CTYPE_OUTDIGIT0_MB, langinfo::_NL_CTYPE_OUTDIGIT1_MB,
+            langinfo::_NL_CTYPE_OUTDIGIT2_MB, langinfo::_NL_CTYPE_OUTDIGIT3_MB,
+            langinfo::_NL_CTYPE_OUTDIGIT4_MB, langinfo::_NL_CTYPE_OUTDIGIT5_MB,
+            langinfo::_NL_CTYPE_OUTDIGIT6_MB, langinfo::_NL_CTYPE_OUTDIGIT7_MB,
+            langinfo::_NL_CTYPE_OUTDIGIT8_MB, langinfo::_NL_CTYPE_OUTDIGIT9_MB,
+        ].iter().map(|&i| self.langinfo(i).into_owned()).collect();
+        if digits.iter().enumerate().all(|(d, s)| *s == d.to_string()) {
+            None
+        } else {
+            Some(digits)
+        }
+    }
+
+    /// Returns the non-ASCII alternatives from input digits of `LC_CTYPE`.
+    ///
+    /// Each of the `_NL_CTYPE_INDIGITS*_MB` items contains `_NL_CTYPE_INDIGITS_MB_LEN`
+    /// null-terminated alternatives, so the `langinfo` method can only access the first of them.
+    fn input_digits(&self) -> Vec<Vec<String>> {
+        let count = self.langinfo(langinfo::_NL_CTYPE_INDIGITS_MB_LEN) as usize;
+        let conv = self.iconv[LibCLocaleFactory::codeset_index(langinfo::_NL_CTYPE_CODESET_NAME)]
+            .as_deref();
+        let mut ptrs: Vec<*const ::libc::c_char> = (0..10)
+            .map(|d| unsafe {
+                ffi::nl_langinfo_l(ffi::_NL_CTYPE_INDIGITS0_MB + d, self.locale.c_locale)
+            })
+            .collect();
+        let mut res = Vec::new();
+        for _ in 0..count {
+            let mut digits = Vec::with_capacity(10);
+            for ptr in &mut ptrs {
+                if ptr.is_null() {
+                    return res;
+                }
+                unsafe {
+                    digits.push(langinfo::decode_string(*ptr, conv).into_owned());
+                    *ptr = ptr.add(CStr::from_ptr(*ptr).to_bytes_with_nul().len());
+                }
+            }
+            if !digits.iter().enumerate().all(|(d, s)| *s == d.to_string()) {
+                res.push(digits);
+            }
+        }
+        res
+    }
+
+    /// Returns the week conventions of `LC_TIME`.
+    ///
+    /// The first weekday and workday are numbered from 1 for the day of week of
+    /// `_NL_TIME_WEEK_1STDAY`, which is a date written as `YYYYMMDD`.
+    fn week_info(&self) -> WeekInfo {
+        let day_one = Date::from_yyyymmdd(self.langinfo(langinfo::_NL_TIME_WEEK_1STDAY)).weekday();
+        let day = |number: i8| day_one.add_days(number as i64 - 1);
+        WeekInfo {
+            first_weekday: day(self.langinfo(langinfo::_NL_TIME_FIRST_WEEKDAY)),
+            min_days_in_first_week: self.langinfo(langinfo::_NL_TIME_WEEK_1STWEEK) as u8,
+            first_workday: day(self.langinfo(langinfo::_NL_TIME_FIRST_WORKDAY)),
+        }
+    }
 }
 
 impl LocaleFactory for LibCLocaleFactory {
     fn get_numeric(&mut self) -> Option<Box<Numeric>> {
-        return Some(
-            Box::new(
-                Numeric::new(
-                    &self.langinfo(langinfo::RADIXCHAR),
-                    &self.langinfo(langinfo::THOUSEP))));
+        let grouping: Vec<u8> = self.langinfo(langinfo::__GROUPING).iter()
+            .map(|&g| g as u8).collect();
+        let mut numeric = Numeric::new_with_grouping(
+            &self.langinfo(langinfo::RADIXCHAR),
+            &self.langinfo(langinfo::THOUSEP),
+            &grouping);
+        numeric.native_digits = self.native_digits();
+        numeric.input_digits = self.input_digits();
+        return Some(Box::new(numeric));
+    }
+
+    fn get_monetary(&mut self) -> Option<Box<Monetary>> {
+        // CHAR_MAX, meaning unspecified, comes out as -1 with signed char
+        fn frac_digits(value: i8) -> u8 {
+            if value < 0 || value == i8::MAX { 2 } else { value as u8 }
+        }
+
+        let grouping: Vec<u8> = self.langinfo(langinfo::__MON_GROUPING).iter()
+            .map(|&g| g as u8).collect();
+        let mut monetary = Monetary {
+            decimal_sep: self.langinfo(langinfo::MON_DECIMAL_POINT).into_owned(),
+            thousands_sep: self.langinfo(langinfo::MON_THOUSANDS_SEP).into_owned(),
+            grouping,
+            positive_sign: self.langinfo(langinfo::POSITIVE_SIGN).into_owned(),
+            negative_sign: self.langinfo(langinfo::NEGATIVE_SIGN).into_owned(),
+            local: CurrencyFormat {
+                symbol: self.langinfo(langinfo::CURRENCY_SYMBOL).into_owned(),
+                frac_digits: frac_digits(self.langinfo(langinfo::__FRAC_DIGITS)),
+                positive: SignFormat::from_lconv(
+                    self.langinfo(langinfo::__P_CS_PRECEDES),
+                    self.langinfo(langinfo::__P_SEP_BY_SPACE),
+                    self.langinfo(langinfo::__P_SIGN_POSN)),
+                negative: SignFormat::from_lconv(
+                    self.langinfo(langinfo::__N_CS_PRECEDES),
+                    self.langinfo(langinfo::__N_SEP_BY_SPACE),
+                    self.langinfo(langinfo::__N_SIGN_POSN)),
+            },
+            international: CurrencyFormat {
+                symbol: self.langinfo(langinfo::INT_CURR_SYMBOL).into_owned(),
+                frac_digits: frac_digits(self.langinfo(langinfo::__INT_FRAC_DIGITS)),
+                positive: SignFormat::from_lconv(
+                    self.langinfo(langinfo::__INT_P_CS_PRECEDES),
+                    self.langinfo(langinfo::__INT_P_SEP_BY_SPACE),
+                    self.langinfo(langinfo::__INT_P_SIGN_POSN)),
+                negative: SignFormat::from_lconv(
+                    self.langinfo(langinfo::__INT_N_CS_PRECEDES),
+                    self.langinfo(langinfo::__INT_N_SEP_BY_SPACE),
+                    self.langinfo(langinfo::__INT_N_SIGN_POSN)),
+            },
+            valid_from: Date::from_yyyymmdd(self.langinfo(langinfo::_NL_MONETARY_UNO_VALID_FROM)),
+            valid_to: Date::from_yyyymmdd(self.langinfo(langinfo::_NL_MONETARY_UNO_VALID_TO)),
+            dual: None,
+        };
+        let dual = Currency {
+            local: CurrencyFormat {
+                symbol: self.langinfo(langinfo::_NL_MONETARY_DUO_CURRENCY_SYMBOL).into_owned(),
+                frac_digits: frac_digits(self.langinfo(langinfo::_NL_MONETARY_DUO_FRAC_DIGITS)),
+                positive: SignFormat::from_lconv(
+                    self.langinfo(langinfo::_NL_MONETARY_DUO_P_CS_PRECEDES),
+                    self.langinfo(langinfo::_NL_MONETARY_DUO_P_SEP_BY_SPACE),
+                    self.langinfo(langinfo::_NL_MONETARY_DUO_P_SIGN_POSN)),
+                negative: SignFormat::from_lconv(
+                    self.langinfo(langinfo::_NL_MONETARY_DUO_N_CS_PRECEDES),
+                    self.langinfo(langinfo::_NL_MONETARY_DUO_N_SEP_BY_SPACE),
+                    self.langinfo(langinfo::_NL_MONETARY_DUO_N_SIGN_POSN)),
+            },
+            international: CurrencyFormat {
+                symbol: self.langinfo(langinfo::_NL_MONETARY_DUO_INT_CURR_SYMBOL).into_owned(),
+                frac_digits: frac_digits(
+                    self.langinfo(langinfo::_NL_MONETARY_DUO_INT_FRAC_DIGITS)),
+                positive: SignFormat::from_lconv(
+                    self.langinfo(langinfo::_NL_MONETARY_DUO_INT_P_CS_PRECEDES),
+                    self.langinfo(langinfo::_NL_MONETARY_DUO_INT_P_SEP_BY_SPACE),
+                    self.langinfo(langinfo::_NL_MONETARY_DUO_INT_P_SIGN_POSN)),
+                negative: SignFormat::from_lconv(
+                    self.langinfo(langinfo::_NL_MONETARY_DUO_INT_N_CS_PRECEDES),
+                    self.langinfo(langinfo::_NL_MONETARY_DUO_INT_N_SEP_BY_SPACE),
+                    self.langinfo(langinfo::_NL_MONETARY_DUO_INT_N_SIGN_POSN)),
+            },
+            valid_from: Date::from_yyyymmdd(self.langinfo(langinfo::_NL_MONETARY_DUO_VALID_FROM)),
+            valid_to: Date::from_yyyymmdd(self.langinfo(langinfo::_NL_MONETARY_DUO_VALID_TO)),
+        };
+        // localedef fills in the dual currency with the primary one when the locale has only one
+        if dual != monetary.currency() {
+            monetary.dual = Some(dual);
+        }
+        Some(Box::new(monetary))
     }
 
     fn get_time(&mut self) -> Option<Box<Time>> {
@@ -269,6 +610,34 @@ impl LocaleFactory for LibCLocaleFactory {
                         self.langinfo(langinfo::MON_11).into_owned(),
                         self.langinfo(langinfo::MON_12).into_owned(),
                     ],
+                    standalone_month_names: vec![
+                        self.langinfo(langinfo::_NL_ABALTMON_1).into_owned(),
+                        self.langinfo(langinfo::_NL_ABALTMON_2).into_owned(),
+                        self.langinfo(langinfo::_NL_ABALTMON_3).into_owned(),
+                        self.langinfo(langinfo::_NL_ABALTMON_4).into_owned(),
+                        self.langinfo(langinfo::_NL_ABALTMON_5).into_owned(),
+                        self.langinfo(langinfo::_NL_ABALTMON_6).into_owned(),
+                        self.langinfo(langinfo::_NL_ABALTMON_7).into_owned(),
+                        self.langinfo(langinfo::_NL_ABALTMON_8).into_owned(),
+                        self.langinfo(langinfo::_NL_ABALTMON_9).into_owned(),
+                        self.langinfo(langinfo::_NL_ABALTMON_10).into_owned(),
+                        self.langinfo(langinfo::_NL_ABALTMON_11).into_owned(),
+                        self.langinfo(langinfo::_NL_ABALTMON_12).into_owned(),
+                    ],
+                    standalone_long_month_names: vec![
+                        self.langinfo(langinfo::ALTMON_1).into_owned(),
+                        self.langinfo(langinfo::ALTMON_2).into_owned(),
+                        self.langinfo(langinfo::ALTMON_3).into_owned(),
+                        self.langinfo(langinfo::ALTMON_4).into_owned(),
+                        self.langinfo(langinfo::ALTMON_5).into_owned(),
+                        self.langinfo(langinfo::ALTMON_6).into_owned(),
+                        self.langinfo(langinfo::ALTMON_7).into_owned(),
+                        self.langinfo(langinfo::ALTMON_8).into_owned(),
+                        self.langinfo(langinfo::ALTMON_9).into_owned(),
+                        self.langinfo(langinfo::ALTMON_10).into_owned(),
+                        self.langinfo(langinfo::ALTMON_11).into_owned(),
+                        self.langinfo(langinfo::ALTMON_12).into_owned(),
+                    ],
                     day_names: vec![
                         self.langinfo(langinfo::ABDAY_1).into_owned(),
                         self.langinfo(langinfo::ABDAY_2).into_owned(),
@@ -287,13 +656,51 @@ impl LocaleFactory for LibCLocaleFactory {
                         self.langinfo(langinfo::DAY_6).into_owned(),
                         self.langinfo(langinfo::DAY_7).into_owned(),
                     ],
+                    date_time_format: self.langinfo(langinfo::D_T_FMT).into_owned(),
+                    date_format: self.langinfo(langinfo::D_FMT).into_owned(),
+                    time_format: self.langinfo(langinfo::T_FMT).into_owned(),
+                    time_ampm_format: self.langinfo(langinfo::T_FMT_AMPM).into_owned(),
+                    am: self.langinfo(langinfo::AM_STR).into_owned(),
+                    pm: self.langinfo(langinfo::PM_STR).into_owned(),
+                    era_date_time_format: self.langinfo(langinfo::ERA_D_T_FMT).into_owned(),
+                    era_date_format: self.langinfo(langinfo::ERA_D_FMT).into_owned(),
+                    era_time_format: self.langinfo(langinfo::ERA_T_FMT).into_owned(),
+                    eras: self.langinfo(langinfo::ERA).iter()
+                        .take(self.langinfo(langinfo::_NL_TIME_ERA_NUM_ENTRIES) as usize)
+                        .filter_map(|entry| Era::parse(entry))
+                        .collect(),
+                    alt_digits: self.langinfo(langinfo::ALT_DIGITS).into_iter()
+                        .map(Cow::into_owned)
+                        .collect(),
+                    week_info: self.week_info(),
+                    calendar_direction: match self.langinfo(langinfo::_NL_TIME_CAL_DIRECTION) {
+                        2 => CalendarDirection::TopToBottom,
+                        3 => CalendarDirection::RightToLeft,
+                        _ => CalendarDirection::LeftToRight,
+                    },
                 }));
     }
+
+    fn get_messages(&mut self) -> Option<Box<Messages>> {
+        // the C locale has no canonical answers, so the English ones are used
+        let english = Messages::english();
+        let yes_str = self.langinfo(langinfo::YESSTR);
+        let no_str = self.langinfo(langinfo::NOSTR);
+        Some(Box::new(Messages::new(
+            &self.langinfo(langinfo::YESEXPR),
+            &self.langinfo(langinfo::NOEXPR),
+            if yes_str.is_empty() { english.yes_str() } else { &yes_str },
+            if no_str.is_empty() { english.no_str() } else { &no_str })))
+    }
+
+    fn get_collator(&mut self) -> Option<Box<Collator>> {
+        Some(Box::new(Collator::new(Box::new(LibCCollation::new(self.locale.clone())))))
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use ::std::ffi::CStr;
+    use ::std::ffi::{CStr,CString};
     use super::*;
 
     fn has_locale(locale: &str) -> bool {
@@ -361,4 +768,398 @@ mod test {
             assert_eq!("Út", lf.langinfo(langinfo::ABDAY_3));
         }
     }
+
+    #[test]
+    fn monetary() {
+        if let Ok(mut lf) = LibCLocaleFactory::new("cs_CZ") {
+            // only test if the host has cs_CZ (non-unicode) locale
+            let monetary = lf.get_monetary().unwrap();
+            assert_eq!("Kč", monetary.local.symbol);
+            assert_eq!("CZK ", monetary.international.symbol);
+            assert_eq!(",", monetary.decimal_sep);
+            assert_eq!(vec![3, 3], monetary.grouping);
+            assert_eq!(2, monetary.local.frac_digits);
+            assert_eq!(SignFormat {
+                cs_precedes: false,
+                sep_by_space: ::SymbolSpacing::SymbolSpace,
+                sign_posn: ::SignPosition::Before,
+            }, monetary.local.negative);
+        }
+        let monetary = LibCLocaleFactory::new("C").unwrap().get_monetary().unwrap();
+        assert_eq!("", monetary.local.symbol);
+        assert_eq!(2, monetary.local.frac_digits);
+        assert_eq!(SignFormat {
+            cs_precedes: true,
+            sep_by_space: ::SymbolSpacing::NoSpace,
+            sign_posn: ::SignPosition::Before,
+        }, monetary.international.positive);
+    }
+
+    #[test]
+    fn monetary_dual() {
+        if let Ok(mut lf) = LibCLocaleFactory::new("de_DE.UTF-8") {
+            let monetary = lf.get_monetary().unwrap();
+            assert_eq!(Date::new(1, 1, 1), monetary.valid_from);
+            assert_eq!(Date::new(9999, 12, 31), monetary.valid_to);
+            // current locale data define no dual currency
+            assert!(monetary.dual.is_none());
+            let monetary = monetary.at_date(Date::new(2024, 2, 29)).unwrap();
+            assert_eq!("\u{20ac}", monetary.local.symbol);
+        }
+    }
+
+    #[test]
+    fn monetary_format() {
+        fn strfmon(loc: &CLocale, format: &str, value: f64) -> String {
+            let format = CString::new(format).unwrap();
+            let mut buf = [0 as ::libc::c_char; 256];
+            unsafe {
+                assert!(ffi::strfmon_l(buf.as_mut_ptr(), buf.len(), loc.c_locale,
+                                       format.as_ptr(), value) >= 0);
+                CStr::from_ptr(buf.as_ptr()).to_str().unwrap().to_owned()
+            }
+        }
+
+        // only the locales the host has are tested
+        for name in &["C", "C.UTF-8", "en_US.UTF-8", "cs_CZ.UTF-8", "de_DE.UTF-8", "fr_FR.UTF-8",
+                      "nl_NL.UTF-8", "it_CH.UTF-8", "pt_PT.UTF-8", "ja_JP.UTF-8", "th_TH.UTF-8",
+                      "hi_IN.UTF-8", "ar_EG.UTF-8", "fa_IR.UTF-8"] {
+            if let Ok(loc) = CLocale::new(name) {
+                let monetary = LibCLocaleFactory::new_from_c_locale(loc.clone())
+                    .get_monetary().unwrap();
+                for &amount in &[0, 1, 99, -150, 100000, -123456789, 1234567890123] {
+                    let local = amount as f64 / 10f64.powi(monetary.local.frac_digits as i32);
+                    assert_eq!(strfmon(&loc, "%n", local), monetary.format(amount), "{}", name);
+                    let int = amount as f64 / 10f64.powi(monetary.international.frac_digits as i32);
+                    assert_eq!(strfmon(&loc, "%i", int), monetary.format_international(amount),
+                               "{}", name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn numeric_native_digits() {
+        if let Ok(mut lf) = LibCLocaleFactory::new("hi_IN.UTF-8") {
+            // only test if the host has hi_IN locale
+            let numeric = lf.get_numeric().unwrap();
+            assert_eq!("१,२३४,५६७", numeric.format_int_native(1234567));
+            assert_eq!(Ok(1234567), numeric.parse_int("१,२३४,५६७"));
+        }
+        if let Ok(mut lf) = LibCLocaleFactory::new("fa_IR.UTF-8") {
+            let numeric = lf.get_numeric().unwrap();
+            assert_eq!(Some("۰۱۲۳۴۵۶۷۸۹".chars().map(|c| c.to_string()).collect()),
+                       numeric.native_digits);
+        }
+        let numeric = LibCLocaleFactory::new("C").unwrap().get_numeric().unwrap();
+        assert_eq!(None, numeric.native_digits);
+        assert!(numeric.input_digits.is_empty());
+    }
+
+    #[test]
+    fn numeric_grouping() {
+        if let Ok(mut lf) = LibCLocaleFactory::new("en_IN.UTF-8") {
+            // only test if the host has en_IN locale
+            let numeric = lf.get_numeric().unwrap();
+            assert_eq!(vec![3, 2], numeric.grouping);
+            assert_eq!("12,34,56,789", numeric.format_int(123456789));
+        }
+        if let Ok(mut lf) = LibCLocaleFactory::new("de_DE.UTF-8") {
+            let numeric = lf.get_numeric().unwrap();
+            assert_eq!("1.234.567,89", numeric.format_float(1234567.89, 2));
+            assert_eq!(Ok(1234567.89), numeric.parse_float("1.234.567,89"));
+        }
+        if let Ok(mut lf) = LibCLocaleFactory::new("cs_CZ.UTF-8") {
+            let numeric = lf.get_numeric().unwrap();
+            assert_eq!(Ok(1234.5), numeric.parse_float("1 234,50"));
+            assert_eq!(Ok(-1234567), numeric.parse_int(&numeric.format_int(-1234567)));
+        }
+        if let Ok(mut lf) = LibCLocaleFactory::new("C") {
+            let numeric = lf.get_numeric().unwrap();
+            assert_eq!("123456789", numeric.format_int(123456789));
+        }
+    }
+
+    #[test]
+    fn time_format() {
+        fn strftime(loc: &CLocale, format: &str, time: &::DateTime) -> String {
+            let date = &time.date;
+            let zone = CString::new(time.zone.clone()).unwrap();
+            let mut tm: ::libc::tm = unsafe { ::std::mem::zeroed() };
+            tm.tm_year = date.year - 1900;
+            tm.tm_mon = date.month as i32 - 1;
+            tm.tm_mday = date.day as i32;
+            tm.tm_hour = time.hour as i32;
+            tm.tm_min = time.minute as i32;
+            tm.tm_sec = time.second as i32;
+            tm.tm_wday = date.day_of_week() as i32;
+            tm.tm_yday = date.day_of_year() as i32 - 1;
+            tm.tm_isdst = if time.utc_offset.is_some() { 0 } else { -1 };
+            tm.tm_gmtoff = time.utc_offset.unwrap_or(0) as ::libc::c_long;
+            tm.tm_zone = zone.as_ptr();
+            let format = CString::new(format).unwrap();
+            let mut buf = [0 as ::libc::c_char; 256];
+            unsafe {
+                ffi::strftime_l(buf.as_mut_ptr(), buf.len(), format.as_ptr(), &tm, loc.c_locale);
+                CStr::from_ptr(buf.as_ptr()).to_str().unwrap().to_owned()
+            }
+        }
+
+        let mut times = vec![
+            ::DateTime::new(::Date::new(2024, 3, 5), 17, 8, 9),
+            ::DateTime::new(::Date::new(1999, 12, 31), 23, 59, 60),
+            ::DateTime::new(::Date::new(2021, 1, 3), 0, 0, 0),
+            ::DateTime::new(::Date::new(2008, 12, 29), 12, 30, 0),
+            ::DateTime::new(::Date::new(-5, 1, 1), 1, 2, 3),
+            ::DateTime::new(::Date::new(10000, 6, 15), 11, 59, 59),
+        ];
+        times[0].utc_offset = Some(3600);
+        times[0].zone = "CET".to_string();
+        times[5].utc_offset = Some(-5430);
+        times[5].zone = "xyz".to_string();
+        let conversions = "aAbBcCdDeFgGhHIjklmMnpPrRStTuUVwWxXyYzZ%";
+        let mut formats: Vec<String> = conversions.chars()
+            .flat_map(|c| vec![format!("%{}", c), format!("%E{}", c), format!("%O{}", c)])
+            .collect();
+        formats.extend(["%_5d|%-m|%05e|%-_3H", "%^a %#A %^B %#b %#p %^P %#Z %^Z", "%12F|%012D",
+                        "%-10c|%^x|%_3X", "%3Ea|%q|%^5q|%#5Eb|x%", "%010Y|%_10G|%-4C|%3y|%-10z",
+                        "%_Ey|%-Ey|%05Ey|%^EC", "%_EY|%-EY|%0EY", "%Oe|%_Oe|%-4Od|%04Om|%Oz"]
+                       .iter().map(|s| s.to_string()));
+
+        // only the locales the host has are tested
+        for name in &["C", "en_US.UTF-8", "cs_CZ.UTF-8", "de_DE.UTF-8", "fr_FR.UTF-8",
+                      "pl_PL.UTF-8", "ru_RU.UTF-8", "hi_IN.UTF-8", "ar_EG.UTF-8", "ja_JP.UTF-8",
+                      "th_TH.UTF-8", "fa_IR.UTF-8"] {
+            if let Ok(loc) = CLocale::new(name) {
+                let mut lf = LibCLocaleFactory::new_from_c_locale(loc.clone());
+                let time = lf.get_time().unwrap();
+                for format in &formats {
+                    for dt in &times {
+                        let expected = strftime(&loc, format, dt);
+                        // C library changes case and counts width in bytes for ASCII only
+                        if !expected.is_ascii() && format.contains(|c: char| {
+                            c == '^' || c == '#' || c.is_ascii_digit()
+                        }) {
+                            continue;
+                        }
+                        assert_eq!(expected, time.format(format, dt), "{} {}", name, format);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn month_and_day_names() {
+        use ::Month::*;
+        use ::Weekday::*;
+        if let Ok(mut lf) = LibCLocaleFactory::new("pl_PL.UTF-8") {
+            let time = lf.get_time().unwrap();
+            assert_eq!("marca", time.long_month_name(March));
+            assert_eq!("marzec", time.standalone_long_month_name(March));
+            assert_eq!("lis", time.short_month_name(November));
+            assert_eq!("lis", time.standalone_short_month_name(November));
+            assert_eq!("wtorek", time.long_day_name(Tuesday));
+            assert_eq!("wto", time.short_day_name(Tuesday));
+            let dt = ::DateTime::new(::Date::new(2024, 3, 5), 17, 8, 9);
+            assert_eq!("5 marca 2024, marzec", time.format("%-d %B %Y, %OB", &dt));
+            assert_eq!(Ok(dt.date), time.parse("%d %B %Y", "5 marzec 2024").map(|t| t.date));
+        }
+        if let Ok(mut lf) = LibCLocaleFactory::new("ru_RU.UTF-8") {
+            let time = lf.get_time().unwrap();
+            assert_eq!("января", time.long_month_name(January));
+            assert_eq!("Январь", time.standalone_long_month_name(January));
+            assert_eq!("мая", time.short_month_name(May));
+            assert_eq!("май", time.standalone_short_month_name(May));
+            assert_eq!("Воскресенье", time.long_day_name(Sunday));
+            assert_eq!("Вс", time.short_day_name(Sunday));
+        }
+        if let Ok(mut lf) = LibCLocaleFactory::new("C") {
+            let time = lf.get_time().unwrap();
+            assert_eq!("December", time.standalone_long_month_name(December));
+            assert_eq!("Saturday", time.long_day_name(Saturday));
+        }
+    }
+
+    #[test]
+    fn month_calendar() {
+        if let Ok(mut lf) = LibCLocaleFactory::new("de_DE.UTF-8") {
+            let time = lf.get_time().unwrap();
+            let calendar = time.month_calendar(2024, ::Month::March, Some(::WeekNumbers::Iso));
+            assert_eq!(::CalendarDirection::LeftToRight, calendar.direction);
+            assert_eq!("März 2024", calendar.title);
+            assert_eq!(::Weekday::Monday, calendar.weekdays[0]);
+            assert_eq!("   Mo Di Mi Do Fr Sa So", calendar.to_string().lines().nth(1).unwrap());
+            assert_eq!(" 9              1  2  3", calendar.to_string().lines().nth(2).unwrap());
+        }
+        if let Ok(mut lf) = LibCLocaleFactory::new("pl_PL.UTF-8") {
+            let time = lf.get_time().unwrap();
+            let calendar = time.month_calendar(2024, ::Month::March, None);
+            assert_eq!("marzec 2024", calendar.title);
+            assert_eq!("po wt śr cz pi so ni", calendar.to_string().lines().nth(1).unwrap());
+        }
+        if let Ok(mut lf) = LibCLocaleFactory::new("fa_IR.UTF-8") {
+            let time = lf.get_time().unwrap();
+            assert_eq!(::CalendarDirection::RightToLeft, time.calendar_direction());
+            let calendar = time.month_calendar(2024, ::Month::March, None);
+            assert_eq!(::Weekday::Saturday, calendar.weekdays[0]);
+            assert_eq!(" 1                  ", calendar.to_string().lines().nth(2).unwrap());
+        }
+    }
+
+    #[test]
+    fn week_info() {
+        use ::Weekday::*;
+        let week_info = |name| LibCLocaleFactory::new(name).ok()
+            .map(|mut lf| lf.get_time().unwrap().week_info().clone());
+        if let Some(week) = week_info("C") {
+            assert_eq!(::WeekInfo::english(), week);
+        }
+        if let Some(week) = week_info("en_US.UTF-8") {
+            assert_eq!((Sunday, 1, Monday),
+                       (week.first_weekday, week.min_days_in_first_week, week.first_workday));
+        }
+        if let Some(week) = week_info("de_DE.UTF-8") {
+            assert_eq!((Monday, 4, Monday),
+                       (week.first_weekday, week.min_days_in_first_week, week.first_workday));
+        }
+        if let Some(week) = week_info("ar_EG.UTF-8") {
+            assert_eq!((Saturday, 1, Sunday),
+                       (week.first_weekday, week.min_days_in_first_week, week.first_workday));
+        }
+    }
+
+    #[test]
+    fn messages() {
+        if let Ok(mut lf) = LibCLocaleFactory::new("C") {
+            let messages = lf.get_messages().unwrap();
+            assert_eq!(("^[yY]", "yes", "no"),
+                       (messages.yes_expr(), messages.yes_str(), messages.no_str()));
+            assert_eq!(Some(false), messages.rpmatch("n"));
+        }
+        for name in &["cs_CZ.UTF-8", "cs_CZ.ISO-8859-2"] {
+            if let Ok(mut lf) = LibCLocaleFactory::new(name) {
+                let messages = lf.get_messages().unwrap();
+                assert_eq!(("ano", "ne"), (messages.yes_str(), messages.no_str()));
+                assert!(messages.matches_yes("ano") && messages.matches_yes("Y"));
+                assert!(messages.matches_no("ne") && !messages.matches_no("ano"));
+                assert_eq!(None, messages.rpmatch("možná"));
+            }
+        }
+        if let Ok(mut lf) = LibCLocaleFactory::new("ja_JP.UTF-8") {
+            let messages = lf.get_messages().unwrap();
+            assert_eq!(Some(true), messages.rpmatch(messages.yes_str()));
+            assert_eq!(Some(false), messages.rpmatch("イイエ"));
+            assert_eq!(Some(true), messages.rpmatch("ｙ"));
+        }
+    }
+
+    #[test]
+    fn catalog_path_templates() {
+        assert_eq!(vec!["/a/cs_CZ.UTF-8@euro/app.cat", "/b/cs-CZ-UTF-8%x%", "app",
+                        "/c/100%/app"],
+                   catalog_paths("app", "cs_CZ.UTF-8@euro",
+                                 "/a/%L/%N.cat:/b/%l-%t-%c%x%::/c/100%%/%N"));
+        assert_eq!(vec!["/de/", "/"], catalog_paths("app", "de", "/%l/%t:/%c"));
+        assert_eq!(vec!["./app.cat"], catalog_paths("./app.cat", "de", "/%l/%N"));
+    }
+
+    #[test]
+    fn message_catalog() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/nls");
+        let nlspath = format!("{0}/%L/%N.cat:{0}/%l/%N.cat", dir);
+        for name in &["cs_CZ.UTF-8", "cs_CZ.ISO-8859-2"] {
+            if let Ok(locale) = CLocale::new(name) {
+                let catalog = MessageCatalog::open_in("test", &locale, &nlspath).unwrap();
+                assert_eq!("Ahoj", catalog.get(1, 1, "Hello"));
+                assert_eq!("Otevřít", catalog.get(1, 2, "Open"));
+                assert_eq!("Soubor nenalezen\n", catalog.get(2, 1, "File not found\n"));
+                assert_eq!("Close", catalog.get(1, 3, "Close"));
+                assert_eq!("Close", catalog.get(3, 1, "Close"));
+            }
+        }
+        if let Ok(locale) = CLocale::new("de_DE.UTF-8") {
+            let error = MessageCatalog::open_in("test", &locale, &nlspath).unwrap_err();
+            assert_eq!(ErrorKind::NotFound, error.kind());
+            let path = format!("{}/cs/test.cat", dir);
+            let catalog = MessageCatalog::open_in(&path, &locale, &nlspath).unwrap();
+            assert_eq!("Ahoj", catalog.get(1, 1, "Hello"));
+        }
+        if let Ok(lf) = LibCLocaleFactory::new("C") {
+            assert!(lf.message_catalog("no-such-catalog").is_err());
+        }
+    }
+
+    #[test]
+    fn collation() {
+        if let Ok(mut lf) = LibCLocaleFactory::new("C") {
+            let collator = lf.get_collator().unwrap();
+            let mut words = vec!["b", "č", "B", "a"];
+            collator.sort(&mut words);
+            assert_eq!(vec!["B", "a", "b", "č"], words);
+        }
+        for name in &["cs_CZ.UTF-8", "cs_CZ.ISO-8859-2"] {
+            if let Ok(mut lf) = LibCLocaleFactory::new(name) {
+                let collator = lf.get_collator().unwrap();
+                let mut words = vec!["chata", "hrad", "Čech", "cibule", "dům", "cukr", "ítem"];
+                collator.sort(&mut words);
+                assert_eq!(vec!["cibule", "cukr", "Čech", "dům", "hrad", "chata", "ítem"], words);
+                assert_eq!(Ordering::Greater, collator.compare("chata", "hrad"));
+                assert_eq!(Ordering::Less, collator.compare("cukr", "čaj"));
+                assert_eq!(Ordering::Equal, collator.compare("dům", "dům"));
+                assert!(collator.sort_key("chata") > collator.sort_key("hrad"));
+                // the comparison ends at NUL, like in C
+                assert_eq!(Ordering::Equal, collator.compare("a\0b", "a\0c"));
+                assert_eq!(Ordering::Less, collator.compare("a\u{65e5}b", "a\u{65e5}c"));
+            }
+        }
+        if let Ok(lf) = LibCLocaleFactory::new("C") {
+            // characters the converter can't handle are replaced rather than cutting the string
+            let collation = LibCCollation {
+                locale: lf.locale.clone(),
+                iconv: IConv::new("ISO-8859-2", "UTF-8").ok(),
+            };
+            let converted = collation.to_c_string("a\u{10d}\u{65e5}b\u{1f600}c");
+            assert_eq!(&b"a\xe8?b?c"[..], converted.as_bytes());
+        }
+    }
+
+    #[test]
+    fn time_parse_round_trip() {
+        let mut times = vec![
+            ::DateTime::new(::Date::new(2024, 3, 5), 17, 8, 9),
+            ::DateTime::new(::Date::new(1999, 12, 31), 23, 59, 60),
+            ::DateTime::new(::Date::new(2021, 1, 3), 0, 0, 0),
+            ::DateTime::new(::Date::new(2008, 9, 29), 12, 30, 1),
+        ];
+        for (dt, &(offset, zone)) in times.iter_mut()
+            .zip(&[(3600, "CET"), (-5400, "NST"), (0, "UTC"), (19800, "IST")])
+        {
+            dt.utc_offset = Some(offset);
+            dt.zone = zone.to_string();
+        }
+        let patterns = ["%c", "%x %X", "%x %r", "%A %d %B %Y %T", "%a %e %b %Y %I:%M:%S %p",
+                        "%^a %#b %G-W%V-%u %T %z %Z", "%D %R:%S", "%Y %U %a %T", "%Y-%j %T",
+                        "%Ec", "%Ex %EX", "%EY-%m-%d", "%d.%m. %Ey %EC",
+                        "%OC %Oy-%Om-%Od %OH:%OM:%OS", "%Y %Oj %OI %p %OM", "%d %OB %Y %Ob"];
+
+        // only the locales the host has are tested
+        for name in &["C", "en_US.UTF-8", "cs_CZ.UTF-8", "de_DE.UTF-8", "fr_FR.UTF-8",
+                      "pl_PL.UTF-8", "ru_RU.UTF-8", "hi_IN.UTF-8", "ar_EG.UTF-8", "ja_JP.UTF-8",
+                      "th_TH.UTF-8", "fa_IR.UTF-8"] {
+            if let Ok(mut lf) = LibCLocaleFactory::new(name) {
+                let time = lf.get_time().unwrap();
+                for pattern in &patterns {
+                    for dt in &times {
+                        let text = time.format(pattern, dt);
+                        let parsed = time.parse(pattern, &text).unwrap_or_else(|e| {
+                            panic!("{} {} {:?}: {}", name, pattern, text, e)
+                        });
+                        assert_eq!(dt.date, parsed.date, "{} {} {:?}", name, pattern, text);
+                        assert_eq!(text, time.format(pattern, &parsed), "{} {}", name, pattern);
+                    }
+                }
+            }
+        }
+    }
 }
@@ -6,7 +6,7 @@ use std::fs::{metadata, File};
 use std::io::{BufRead, Error, Result, BufReader};
 use std::path::{Path, PathBuf};
 
-use super::{LocaleFactory, Numeric, Time};
+use super::{CalendarDirection, LocaleFactory, Messages, Numeric, Time, WeekInfo};
 
 /// The directory inside which locale files are found.
 ///
@@ -16,7 +16,7 @@ static LOCALE_DIR: &'static str = "/usr/share/locale";
 
 #[derive(Debug, Clone)]
 enum LocaleType {
-    Numeric, Time,
+    Numeric, Time, Messages,
 }
 
 fn find_user_locale_path(file_name: &str) -> Option<PathBuf> {
@@ -53,6 +53,7 @@ fn find_locale_path(locale_type: LocaleType, locale_name: &str) -> Option<PathBu
     let file_name = match locale_type {
         LocaleType::Numeric => "LC_NUMERIC",
         LocaleType::Time    => "LC_TIME",
+        LocaleType::Messages => "LC_MESSAGES/LC_MESSAGES",
     };
 
     if locale_name == "" {
@@ -76,9 +77,25 @@ fn load_numeric(locale: &str) -> Result<Numeric> {
         let file = BufReader::new(try!(File::open(&path)));
         let lines: Vec<String> = file.lines().map(|x| x.unwrap()).collect();
 
+        // The grouping is stored as semicolon-separated list like "3;3", with -1 meaning no
+        // further grouping.
+        let grouping = match lines.get(2) {
+            Some(g) => g.trim().split(';')
+                .filter_map(|n| n.trim().parse::<i32>().ok())
+                .map(|n| if (0..=127).contains(&n) { n as u8 } else { 127 })
+                .collect(),
+            None => vec![3],
+        };
+
         Ok(Numeric {
             decimal_sep: lines[0].trim().to_string(),
             thousands_sep: lines[1].trim().to_string(),
+            grouping,
+            negative_sign: "-".to_string(),
+            nan: "nan".to_string(),
+            infinity: "inf".to_string(),
+            native_digits: None,
+            input_digits: Vec::new(),
         })
     }
     else {
@@ -93,16 +110,36 @@ fn load_time(locale: &str) -> Result<Time> {
         let file = BufReader::new(try!(File::open(&path)));
         let mut iter = file.lines().map(|x| x.unwrap().trim().to_string());
 
-        let month_names      = iter.by_ref().take(12).collect();
-        let long_month_names = iter.by_ref().take(12).collect();
+        let month_names: Vec<String>      = iter.by_ref().take(12).collect();
+        let long_month_names: Vec<String> = iter.by_ref().take(12).collect();
         let day_names        = iter.by_ref().take(7).collect();
         let long_day_names   = iter.by_ref().take(7).collect();
 
         Ok(Time {
+            standalone_month_names: month_names.clone(),
             month_names:      month_names,
-            long_month_names: long_month_names,
             day_names:        day_names,
             long_day_names:   long_day_names,
+            time_format:      iter.next().unwrap_or_default(),
+            date_format:      iter.next().unwrap_or_default(),
+            date_time_format: iter.next().unwrap_or_default(),
+            am:               iter.next().unwrap_or_default(),
+            pm:               iter.next().unwrap_or_default(),
+            // skip date(1) format, the alternative month names are the ones for `%OB`
+            standalone_long_month_names: {
+                let names: Vec<String> = iter.by_ref().skip(1).take(12).collect();
+                if names.len() == 12 { names } else { long_month_names.clone() }
+            },
+            long_month_names: long_month_names,
+            // skip month/day order
+            time_ampm_format: iter.nth(1).unwrap_or_default(),
+            era_date_time_format: String::new(),
+            era_date_format:      String::new(),
+            era_time_format:      String::new(),
+            eras:                 Vec::new(),
+            alt_digits:           Vec::new(),
+            week_info:            WeekInfo::english(),
+            calendar_direction:   CalendarDirection::LeftToRight,
         })
     }
     else {
@@ -110,6 +147,24 @@ fn load_time(locale: &str) -> Result<Time> {
     }
 }
 
+fn load_messages(locale: &str) -> Result<Messages> {
+    let path = find_locale_path(LocaleType::Messages, locale);
+
+    if let Some(path) = path {
+        let file = BufReader::new(File::open(&path)?);
+        let lines: Vec<String> = file.lines().map(|x| x.unwrap().trim().to_string()).collect();
+        let line = |index: usize, default: &'static str| match lines.get(index) {
+            Some(line) if !line.is_empty() => line.clone(),
+            _ => default.to_string(),
+        };
+
+        Ok(Messages::new(&line(0, "^[yY]"), &line(1, "^[nN]"), &line(2, "yes"), &line(3, "no")))
+    }
+    else {
+        Err(Error::last_os_error())
+    }
+}
+
 pub struct MacOSLocaleFactory {
     locale: String,
 }
@@ -139,6 +194,14 @@ impl LocaleFactory for MacOSLocaleFactory {
             None
         }
     }
+
+    fn get_messages(&mut self) -> Option<Box<Messages>> {
+        if let Ok(messages) = load_messages(&self.locale) {
+            Some(Box::new(messages))
+        } else {
+            None
+        }
+    }
 }
 
 // ---- PathExt replacement ----
@@ -0,0 +1,659 @@
+//! Time zones from the system zoneinfo database.
+//!
+//! Reads the time zone information files (TZif, described in RFC 8536) installed in
+//! `/usr/share/zoneinfo` and time zone rules in the format of the POSIX `TZ` environment variable,
+//! and converts UTC timestamps to local time. The resulting `DateTime` carries the offset and
+//! abbreviation, so `%z` and `%Z` of `Time::format` print them.
+
+use ::std::env;
+use ::std::fs;
+use ::std::io::{Error, ErrorKind, Result};
+use ::std::path::{Path, PathBuf};
+use super::{days_in_month, is_leap_year, Date, DateTime, MAX_TIMESTAMP, MIN_TIMESTAMP};
+
+/// The default location of the zoneinfo database, used unless `TZDIR` is set.
+pub const ZONEINFO_DIR: &str = "/usr/share/zoneinfo";
+
+/// A kind of local time used in a time zone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalTimeType {
+    /// Offset from UTC in seconds, positive east of Greenwich.
+    pub utc_offset: i32,
+
+    /// Whether this is daylight saving time.
+    pub is_dst: bool,
+
+    /// Abbreviated name, like `CEST`.
+    pub abbreviation: String,
+}
+
+/// Rules of a time zone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeZone {
+    /// Times of changes of the local time type, ascending.
+    transitions: Vec<i64>,
+    /// Index into `types` for each transition.
+    transition_types: Vec<usize>,
+    /// The first one is in effect before the first transition.
+    types: Vec<LocalTimeType>,
+    /// Times of leap seconds with the total correction from then on.
+    leap_seconds: Vec<(i64, i64)>,
+    /// Rule for times after the last transition.
+    rule: Option<PosixRule>,
+}
+
+impl TimeZone {
+    /// Constructs the UTC time zone.
+    pub fn utc() -> TimeZone {
+        TimeZone::fixed(LocalTimeType {
+            utc_offset: 0,
+            is_dst: false,
+            abbreviation: "UTC".to_string(),
+        })
+    }
+
+    /// Returns the time zone selected for the process.
+    ///
+    /// Like the C library, this uses the `TZ` environment variable if set and `/etc/localtime`
+    /// otherwise, and falls back to UTC if neither can be used.
+    pub fn local() -> TimeZone {
+        let zone = match env::var_os("TZ") {
+            Some(value) => value.to_str().map_or_else(
+                || Err(Error::new(ErrorKind::InvalidInput, "TZ is not valid unicode")),
+                TimeZone::from_tz),
+            None => TimeZone::from_file("/etc/localtime"),
+        };
+        zone.unwrap_or_else(|_| TimeZone::utc())
+    }
+
+    /// Interprets a value of the `TZ` environment variable.
+    ///
+    /// Empty value means UTC. A value starting with `:` names a zoneinfo file. Otherwise the
+    /// value is tried as a zoneinfo file first and as a POSIX rule, like
+    /// `CET-1CEST,M3.5.0,M10.5.0/3`, second.
+    pub fn from_tz(value: &str) -> Result<TimeZone> {
+        if value.is_empty() {
+            Ok(TimeZone::utc())
+        } else if let Some(name) = value.strip_prefix(':') {
+            TimeZone::load(name)
+        } else {
+            TimeZone::load(value).or_else(|err| TimeZone::from_posix(value).map_err(|_| err))
+        }
+    }
+
+    /// Loads the time zone of given name, like `Europe/Prague`, from the zoneinfo database.
+    ///
+    /// The database is looked up in the directory given by the `TZDIR` environment variable, or
+    /// `ZONEINFO_DIR` if it is not set. Absolute paths are used as is.
+    pub fn load(name: &str) -> Result<TimeZone> {
+        let dir = env::var_os("TZDIR").map_or_else(|| PathBuf::from(ZONEINFO_DIR), PathBuf::from);
+        TimeZone::load_from(dir, name)
+    }
+
+    /// Loads the time zone of given name from a zoneinfo database in directory `dir`.
+    pub fn load_from<P: AsRef<Path>>(dir: P, name: &str) -> Result<TimeZone> {
+        // the name must not lead out of the database
+        if name.split('/').any(|part| part == "..") {
+            return Err(Error::new(ErrorKind::InvalidInput, "invalid time zone name"));
+        }
+        TimeZone::from_file(dir.as_ref().join(name))
+    }
+
+    /// Reads the time zone from a TZif file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<TimeZone> {
+        TimeZone::from_tzif(&fs::read(path)?)
+    }
+
+    /// Parses the time zone from contents of a TZif file.
+    ///
+    /// Versions 1 to 4 of the format are supported. For version 2 and later the 64-bit data and
+    /// the rule in the footer are used.
+    pub fn from_tzif(data: &[u8]) -> Result<TimeZone> {
+        let mut reader = Reader { data };
+        let header = Header::read(&mut reader)?;
+        if header.version < b'2' {
+            return TimeZone::read_data(&mut reader, &header, 4);
+        }
+        reader.take(header.data_len(4).ok_or_else(invalid_data)?)?;
+        let header = Header::read(&mut reader)?;
+        let mut zone = TimeZone::read_data(&mut reader, &header, 8)?;
+        if reader.take(1)? != b"\n" {
+            return Err(invalid_data());
+        }
+        let footer_len = reader.data.iter().position(|&b| b == b'\n').ok_or_else(invalid_data)?;
+        let footer = reader.take(footer_len)?;
+        if !footer.is_empty() {
+            let footer = ::std::str::from_utf8(footer).map_err(|_| invalid_data())?;
+            zone.rule = Some(PosixRule::parse(footer).ok_or_else(invalid_data)?);
+        }
+        Ok(zone)
+    }
+
+    /// Constructs the time zone from a POSIX rule, like `EST5EDT,M3.2.0,M11.1.0`.
+    ///
+    /// Offsets in the rule are positive west of Greenwich. Missing daylight saving time offset
+    /// is one hour ahead of the standard time and missing transition dates are those of the USA.
+    /// Transition times may be negative or over 24 hours as allowed by RFC 8536.
+    pub fn from_posix(rule: &str) -> Result<TimeZone> {
+        let rule = PosixRule::parse(rule)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "invalid time zone rule"))?;
+        let mut zone = TimeZone::fixed(rule.std.clone());
+        zone.rule = Some(rule);
+        Ok(zone)
+    }
+
+    /// Returns the local time type in effect at given number of seconds since 1970-01-01 00:00:00
+    /// UTC.
+    pub fn local_time_type(&self, timestamp: i64) -> &LocalTimeType {
+        if let Some(ref rule) = self.rule {
+            if self.transitions.last().filter(|&&last| timestamp < last).is_none() {
+                return rule.local_time_type(timestamp);
+            }
+        }
+        match self.transitions.binary_search(&timestamp) {
+            Ok(index) => &self.types[self.transition_types[index]],
+            Err(0) => &self.types[0],
+            Err(index) => &self.types[self.transition_types[index - 1]],
+        }
+    }
+
+    /// Converts number of seconds since 1970-01-01 00:00:00 UTC to local time.
+    ///
+    /// For zones with leap seconds, like those in `right/`, the leap second shows as second 60
+    /// and `timestamp` of the result does not count leap seconds.
+    pub fn to_local(&self, timestamp: i64) -> DateTime {
+        let mut correction = 0;
+        let mut hit = false;
+        if let Some(index) = self.leap_seconds.iter().rposition(|&(time, _)| time <= timestamp) {
+            let before = if index > 0 { self.leap_seconds[index - 1].1 } else { 0 };
+            correction = self.leap_seconds[index].1;
+            hit = self.leap_seconds[index].0 == timestamp && correction > before;
+        }
+        let local_time_type = self.local_time_type(timestamp);
+        let mut time = DateTime::from_timestamp(timestamp.saturating_sub(correction),
+                                                local_time_type.utc_offset);
+        if hit {
+            time.second += 1;
+        }
+        time.zone = local_time_type.abbreviation.clone();
+        time
+    }
+
+    fn fixed(local_time_type: LocalTimeType) -> TimeZone {
+        TimeZone {
+            transitions: Vec::new(),
+            transition_types: Vec::new(),
+            types: vec![local_time_type],
+            leap_seconds: Vec::new(),
+            rule: None,
+        }
+    }
+
+    fn read_data(reader: &mut Reader, header: &Header, time_size: usize) -> Result<TimeZone> {
+        // the counts are checked before allocating for them
+        if header.data_len(time_size).filter(|&len| len <= reader.data.len()).is_none() {
+            return Err(invalid_data());
+        }
+        let mut transitions = Vec::with_capacity(header.timecnt);
+        for _ in 0..header.timecnt {
+            let time = reader.int(time_size)?;
+            if transitions.last().filter(|&&last| last >= time).is_some() {
+                return Err(invalid_data());
+            }
+            transitions.push(time);
+        }
+        let transition_types = reader.take(header.timecnt)?.iter()
+            .map(|&index| index as usize)
+            .collect::<Vec<_>>();
+        if transition_types.iter().any(|&index| index >= header.typecnt) {
+            return Err(invalid_data());
+        }
+        let mut types = Vec::with_capacity(header.typecnt);
+        for _ in 0..header.typecnt {
+            let utc_offset = reader.int(4)? as i32;
+            let is_dst = match reader.take(1)?[0] {
+                0 => false,
+                1 => true,
+                _ => return Err(invalid_data()),
+            };
+            types.push((utc_offset, is_dst, reader.take(1)?[0] as usize));
+        }
+        let chars = reader.take(header.charcnt)?;
+        let types = types.into_iter()
+            .map(|(utc_offset, is_dst, index)| {
+                let len = chars.get(index..)
+                    .and_then(|rest| rest.iter().position(|&b| b == 0))
+                    .ok_or_else(invalid_data)?;
+                let abbreviation = String::from_utf8_lossy(&chars[index..index + len]).into_owned();
+                Ok(LocalTimeType { utc_offset, is_dst, abbreviation })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut leap_seconds = Vec::with_capacity(header.leapcnt);
+        for _ in 0..header.leapcnt {
+            let time = reader.int(time_size)?;
+            leap_seconds.push((time, reader.int(4)?));
+        }
+        reader.take(header.isstdcnt + header.isutcnt)?;
+        Ok(TimeZone { transitions, transition_types, types, leap_seconds, rule: None })
+    }
+}
+
+fn invalid_data() -> Error {
+    Error::new(ErrorKind::InvalidData, "invalid TZif data")
+}
+
+/// Cursor over the TZif data.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.data.len() {
+            return Err(invalid_data());
+        }
+        let (result, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(result)
+    }
+
+    /// Reads big-endian signed number of `size` bytes.
+    fn int(&mut self, size: usize) -> Result<i64> {
+        let bytes = self.take(size)?;
+        let value = bytes.iter().fold(0u64, |value, &b| value << 8 | b as u64);
+        let shift = 64 - 8 * size as u32;
+        Ok((value << shift) as i64 >> shift)
+    }
+}
+
+/// Header of a TZif data block.
+struct Header {
+    version: u8,
+    isutcnt: usize,
+    isstdcnt: usize,
+    leapcnt: usize,
+    timecnt: usize,
+    typecnt: usize,
+    charcnt: usize,
+}
+
+impl Header {
+    fn read(reader: &mut Reader) -> Result<Header> {
+        if reader.take(4)? != b"TZif" {
+            return Err(invalid_data());
+        }
+        let version = reader.take(16)?[0];
+        let mut count = || reader.int(4).map(|n| n as u32 as usize);
+        let header = Header {
+            version: if version == 0 { b'1' } else { version },
+            isutcnt: count()?,
+            isstdcnt: count()?,
+            leapcnt: count()?,
+            timecnt: count()?,
+            typecnt: count()?,
+            charcnt: count()?,
+        };
+        if header.typecnt == 0 || header.charcnt == 0
+            || header.isutcnt != 0 && header.isutcnt != header.typecnt
+            || header.isstdcnt != 0 && header.isstdcnt != header.typecnt
+        {
+            return Err(invalid_data());
+        }
+        Ok(header)
+    }
+
+    /// Returns length of the data block with times of `time_size` bytes, `None` on overflow.
+    fn data_len(&self, time_size: usize) -> Option<usize> {
+        [
+            self.timecnt.checked_mul(time_size + 1),
+            self.typecnt.checked_mul(6),
+            Some(self.charcnt),
+            self.leapcnt.checked_mul(time_size + 4),
+            Some(self.isstdcnt),
+            Some(self.isutcnt),
+        ].iter().try_fold(0usize, |len, &part| len.checked_add(part?))
+    }
+}
+
+/// Time zone rule in the format of the POSIX `TZ` variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PosixRule {
+    std: LocalTimeType,
+    dst: Option<DstRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DstRule {
+    dst: LocalTimeType,
+    /// Start of daylight saving time, given in standard time.
+    start: (RuleDate, i32),
+    /// End of daylight saving time, given in daylight saving time.
+    end: (RuleDate, i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleDate {
+    /// `Jn`: day of the year from 1, not counting February 29th.
+    Julian(u16),
+    /// `n`: day of the year from 0, counting February 29th.
+    ZeroBased(u16),
+    /// `Mm.w.d`: day `d` of week `w` of month `m`, with week 5 meaning the last one.
+    MonthWeekDay(u8, u8, u8),
+}
+
+impl RuleDate {
+    fn days_since_epoch(self, year: i32) -> i64 {
+        let first = Date::new(year, 1, 1).days_since_epoch();
+        match self {
+            RuleDate::Julian(day) => {
+                first + day as i64 - if is_leap_year(year) && day >= 60 { 0 } else { 1 }
+            }
+            RuleDate::ZeroBased(day) => first + day as i64,
+            RuleDate::MonthWeekDay(month, week, weekday) => {
+                let first = Date::new(year, month, 1);
+                let mut day = 1 + (weekday + 7 - first.day_of_week()) % 7 + (week - 1) * 7;
+                while day > days_in_month(year, month) {
+                    day -= 7;
+                }
+                first.days_since_epoch() + day as i64 - 1
+            }
+        }
+    }
+}
+
+impl PosixRule {
+    fn parse(rule: &str) -> Option<PosixRule> {
+        let mut parser = RuleParser { input: rule.as_bytes(), pos: 0 };
+        let std = LocalTimeType {
+            abbreviation: parser.name()?,
+            utc_offset: -parser.offset(24)?,
+            is_dst: false,
+        };
+        if parser.at_end() {
+            return Some(PosixRule { std, dst: None });
+        }
+        let abbreviation = parser.name()?;
+        let utc_offset = if parser.at_end() || parser.peek() == Some(b',') {
+            std.utc_offset + 3600
+        } else {
+            -parser.offset(24)?
+        };
+        let (start, end) = if parser.at_end() {
+            ((RuleDate::MonthWeekDay(3, 2, 0), 7200), (RuleDate::MonthWeekDay(11, 1, 0), 7200))
+        } else {
+            parser.expect(b',')?;
+            let start = (parser.date()?, parser.time()?);
+            parser.expect(b',')?;
+            (start, (parser.date()?, parser.time()?))
+        };
+        if !parser.at_end() {
+            return None;
+        }
+        let dst = LocalTimeType { utc_offset, is_dst: true, abbreviation };
+        Some(PosixRule { std, dst: Some(DstRule { dst, start, end }) })
+    }
+
+    fn local_time_type(&self, timestamp: i64) -> &LocalTimeType {
+        let rule = match self.dst {
+            Some(ref rule) => rule,
+            None => return &self.std,
+        };
+        let std_offset = self.std.utc_offset as i64;
+        let local = timestamp.saturating_add(std_offset).clamp(MIN_TIMESTAMP, MAX_TIMESTAMP);
+        let year = Date::from_days_since_epoch(local.div_euclid(86400)).year;
+        let transition = |(date, time): (RuleDate, i32), offset: i64| {
+            date.days_since_epoch(year) * 86400 + time as i64 - offset
+        };
+        let start = transition(rule.start, std_offset);
+        let end = transition(rule.end, rule.dst.utc_offset as i64);
+        let is_dst = if start <= end {
+            start <= timestamp && timestamp < end
+        } else {
+            // southern hemisphere
+            timestamp < end || start <= timestamp
+        };
+        if is_dst { &rule.dst } else { &self.std }
+    }
+}
+
+struct RuleParser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> RuleParser<'a> {
+    fn at_end(&self) -> bool {
+        self.pos == self.input.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).cloned()
+    }
+
+    fn expect(&mut self, b: u8) -> Option<()> {
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn take_while<F: Fn(u8) -> bool>(&mut self, predicate: F) -> &'a [u8] {
+        let start = self.pos;
+        while self.peek().filter(|&b| predicate(b)).is_some() {
+            self.pos += 1;
+        }
+        &self.input[start..self.pos]
+    }
+
+    /// Reads zone abbreviation, either at least three letters or anything quoted in `<>`.
+    fn name(&mut self) -> Option<String> {
+        let name = if self.expect(b'<').is_some() {
+            let name = self.take_while(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'-');
+            self.expect(b'>')?;
+            name
+        } else {
+            self.take_while(|b| b.is_ascii_alphabetic())
+        };
+        if name.len() < 3 {
+            return None;
+        }
+        Some(String::from_utf8_lossy(name).into_owned())
+    }
+
+    fn number(&mut self, max: u32) -> Option<u32> {
+        let digits = self.take_while(|b| b.is_ascii_digit());
+        if digits.is_empty() || digits.len() > 3 {
+            return None;
+        }
+        let value = digits.iter().fold(0, |value, &b| value * 10 + (b - b'0') as u32);
+        if value <= max { Some(value) } else { None }
+    }
+
+    /// Reads `[+-]hh[:mm[:ss]]` with hours up to `max_hours` and returns it in seconds.
+    fn offset(&mut self, max_hours: u32) -> Option<i32> {
+        let sign = if self.expect(b'-').is_some() {
+            -1
+        } else {
+            self.expect(b'+');
+            1
+        };
+        let mut seconds = self.number(max_hours)? * 3600;
+        if self.expect(b':').is_some() {
+            seconds += self.number(59)? * 60;
+            if self.expect(b':').is_some() {
+                seconds += self.number(59)?;
+            }
+        }
+        Some(sign * seconds as i32)
+    }
+
+    fn date(&mut self) -> Option<RuleDate> {
+        if self.expect(b'J').is_some() {
+            match self.number(365)? {
+                0 => None,
+                day => Some(RuleDate::Julian(day as u16)),
+            }
+        } else if self.expect(b'M').is_some() {
+            let month = self.number(12)?;
+            self.expect(b'.')?;
+            let week = self.number(5)?;
+            self.expect(b'.')?;
+            let weekday = self.number(6)?;
+            if month == 0 || week == 0 {
+                return None;
+            }
+            Some(RuleDate::MonthWeekDay(month as u8, week as u8, weekday as u8))
+        } else {
+            Some(RuleDate::ZeroBased(self.number(365)? as u16))
+        }
+    }
+
+    /// Reads the optional `/time` after a date, defaulting to 02:00.
+    fn time(&mut self) -> Option<i32> {
+        if self.expect(b'/').is_some() {
+            self.offset(167)
+        } else {
+            Some(7200)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ::Time;
+
+    fn fixtures() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/zoneinfo")
+    }
+
+    fn local(zone: &TimeZone, timestamp: i64) -> String {
+        Time::english().format("%F %T %z %Z", &zone.to_local(timestamp))
+    }
+
+    #[test]
+    fn tzif_version_2() {
+        let zone = TimeZone::load_from(fixtures(), "Europe/Prague").unwrap();
+        assert_eq!("1850-01-01 00:57:44 +0057 PMT", local(&zone, -3786825600));
+        assert_eq!("1916-04-30 22:59:59 +0100 CET", local(&zone, -1693706401));
+        assert_eq!("1916-05-01 00:00:00 +0200 CEST", local(&zone, -1693706400));
+        assert_eq!("2024-01-01 01:00:00 +0100 CET", local(&zone, 1704067200));
+        // after the last transition, from the footer
+        assert_eq!("2100-12-07 01:00:00 +0100 CET", local(&zone, 4131820800));
+        let dt = zone.to_local(1909224000);
+        assert_eq!("2030-07-02 14:00:00 +0200 CEST", Time::english().format("%F %T %z %Z", &dt));
+        assert_eq!(1909224000, dt.timestamp());
+        assert!(zone.local_time_type(1909224000).is_dst);
+    }
+
+    #[test]
+    fn tzif_version_1() {
+        let zone = TimeZone::load_from(fixtures(), "Test/Version1").unwrap();
+        assert_eq!("1850-01-01 00:57:44 +0057 LMT", local(&zone, -3786825600));
+        assert_eq!("1916-05-01 00:00:00 +0200 CEST", local(&zone, -1693706400));
+        assert_eq!("2024-07-01 02:00:00 +0200 CEST", local(&zone, 1719792000));
+    }
+
+    #[test]
+    fn tzif_southern_hemisphere() {
+        let zone = TimeZone::load_from(fixtures(), "Australia/Lord_Howe").unwrap();
+        assert_eq!("1850-01-01 10:36:20 +1036 LMT", local(&zone, -3786825600));
+        assert_eq!("2024-01-01 11:00:00 +1100 +11", local(&zone, 1704067200));
+        assert_eq!("2024-07-01 10:30:00 +1030 +1030", local(&zone, 1719792000));
+        assert_eq!("2030-07-02 22:30:00 +1030 +1030", local(&zone, 1909224000));
+        assert_eq!("2100-12-07 11:00:00 +1100 +11", local(&zone, 4131820800));
+    }
+
+    #[test]
+    fn tzif_slim() {
+        let zone = TimeZone::load_from(fixtures(), "Test/Slim").unwrap();
+        assert_eq!("1849-12-31 22:30:00 -0130 NST", local(&zone, -3786825600));
+        assert_eq!("2001-03-24 23:29:59 -0130 NST", local(&zone, 985481999));
+        assert_eq!("2001-03-25 00:30:00 -0030 NDT", local(&zone, 985482000));
+        assert_eq!("2030-07-02 11:30:00 -0030 NDT", local(&zone, 1909224000));
+        assert_eq!("2100-12-06 22:30:00 -0130 NST", local(&zone, 4131820800));
+        let zone = TimeZone::load_from(fixtures(), "Test/Fixed").unwrap();
+        assert_eq!("2024-01-01 05:45:00 +0545 +0545", local(&zone, 1704067200));
+    }
+
+    #[test]
+    fn tzif_leap_seconds() {
+        let zone = TimeZone::load_from(fixtures(), "right/UTC").unwrap();
+        assert_eq!("1972-06-30 23:59:59 +0000 UTC", local(&zone, 78796799));
+        assert_eq!("1972-06-30 23:59:60 +0000 UTC", local(&zone, 78796800));
+        assert_eq!("1972-07-01 00:00:00 +0000 UTC", local(&zone, 78796801));
+    }
+
+    #[test]
+    fn tzif_errors() {
+        let data = fs::read(fixtures().join("Europe/Prague")).unwrap();
+        let kind = |data: &[u8]| TimeZone::from_tzif(data).unwrap_err().kind();
+        assert_eq!(ErrorKind::InvalidData, kind(&data[..data.len() - 1]));
+        assert_eq!(ErrorKind::InvalidData, kind(&data[..100]));
+        assert_eq!(ErrorKind::InvalidData, kind(b"TZjf2"));
+        // counts far beyond the data are rejected without allocating for them
+        let mut huge_counts = data[..44].to_vec();
+        huge_counts[4] = b'\0';
+        huge_counts[32..36].copy_from_slice(&[0xFF; 4]);
+        assert_eq!(ErrorKind::InvalidData, kind(&huge_counts));
+        huge_counts[28..32].copy_from_slice(&[0xFF; 4]);
+        assert_eq!(ErrorKind::InvalidData, kind(&huge_counts));
+        huge_counts[4] = b'2';
+        assert_eq!(ErrorKind::InvalidData, kind(&huge_counts));
+        let mut bad_footer = data.clone();
+        let len = bad_footer.len();
+        bad_footer[len - 3] = b'x';
+        assert_eq!(ErrorKind::InvalidData, kind(&bad_footer));
+        assert_eq!(ErrorKind::InvalidInput,
+                   TimeZone::load_from(fixtures(), "../zoneinfo/Test/Slim").unwrap_err().kind());
+        assert!(TimeZone::load_from(fixtures(), "Test/Missing").is_err());
+    }
+
+    #[test]
+    fn posix_rules() {
+        let zone = TimeZone::from_posix("EST5EDT").unwrap();
+        assert_eq!("2024-03-10 01:59:59 -0500 EST", local(&zone, 1710053999));
+        assert_eq!("2024-03-10 03:00:00 -0400 EDT", local(&zone, 1710054000));
+        assert_eq!("2024-11-03 01:59:59 -0400 EDT", local(&zone, 1730613599));
+        assert_eq!("2024-11-03 01:00:00 -0500 EST", local(&zone, 1730613600));
+
+        let zone = TimeZone::from_posix("<+1030>-10:30<+11>-11,M10.1.0,M4.1.0").unwrap();
+        assert_eq!("2024-01-01 11:00:00 +1100 +11", local(&zone, 1704067200));
+        assert_eq!("2024-07-01 10:30:00 +1030 +1030", local(&zone, 1719792000));
+
+        assert_eq!("2147483647-12-31 23:59:59 +1100 +11", local(&zone, i64::MAX));
+        assert_eq!("-2147483648-01-01 00:00:00 +1100 +11", local(&zone, i64::MIN));
+
+        let zone = TimeZone::from_posix("NST1:30NDT,M3.5.0/-0:30,M10.1.0/0:30").unwrap();
+        assert_eq!("2001-03-24 23:29:59 -0130 NST", local(&zone, 985481999));
+        assert_eq!("2001-03-25 00:30:00 -0030 NDT", local(&zone, 985482000));
+
+        // `J60` is March 1st in every year, while `59` is February 29th in leap years
+        let zone = TimeZone::from_posix("AAA0BBB,J60/0,300").unwrap();
+        assert_eq!("2024-02-29 12:00:00 +0000 AAA", local(&zone, 1709208000));
+        assert_eq!("2024-03-01 01:00:00 +0100 BBB", local(&zone, 1709251200));
+        let zone = TimeZone::from_posix("AAA0BBB,59/0,300").unwrap();
+        assert_eq!("2024-02-29 13:00:00 +0100 BBB", local(&zone, 1709208000));
+        assert_eq!("2023-03-01 01:00:00 +0100 BBB", local(&zone, 1677628800));
+
+        assert_eq!(TimeZone::utc(), TimeZone::from_tz("").unwrap());
+        assert_eq!(TimeZone::from_posix("UTC0").unwrap().local_time_type(0),
+                   TimeZone::utc().local_time_type(0));
+        for rule in &["EST", "AB5", "EST5EDT,M13.1.0,M11.1.0", "<+05", "EST5EDT,M3.2.0/168,M11.1.0",
+                      "EST5EDT,J0,J365", "EST5EDT,M3.2.0", "EST25", "EST5EDT4,M3.2.0,M11.1.0x"] {
+            assert!(TimeZone::from_posix(rule).is_err(), "{}", rule);
+        }
+    }
+
+    #[test]
+    fn tz_variable() {
+        let path = fixtures().join("Test/Slim");
+        let zone = TimeZone::from_tz(&format!(":{}", path.display())).unwrap();
+        assert_eq!(TimeZone::from_file(&path).unwrap(), zone);
+        assert_eq!(TimeZone::from_posix("CET-1CEST,M3.5.0,M10.5.0/3").unwrap(),
+                   TimeZone::from_tz("CET-1CEST,M3.5.0,M10.5.0/3").unwrap());
+        assert!(TimeZone::from_tz(":CET-1CEST,M3.5.0,M10.5.0/3").is_err());
+    }
+}
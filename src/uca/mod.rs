@@ -0,0 +1,389 @@
+//! Unicode Collation Algorithm, ordering strings without relying on the system locales.
+//!
+//! This implements the algorithm of [UTS #10](https://www.unicode.org/reports/tr10/), version
+//! 13.0.0, with the bundled Default Unicode Collation Element Table (DUCET). The table is not
+//! tailored to any language, so the order is reasonable for most languages, but not exactly what
+//! any of them uses. The module is only available with the `uca` feature.
+//!
+//! The `UcaCollation` plugs into the `Collator` facet, either directly with `Collator::new`, or
+//! through the `UcaLocaleFactory`.
+
+use ::std::cmp::Ordering;
+
+use super::{Collation, Collator, LocaleFactory};
+use ::std::io::Result;
+
+mod normalize;
+mod table;
+
+use self::normalize::combining_class;
+use self::table::{Element, Table};
+
+/// The most detailed level of differences the collation takes into account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Strength {
+    /// Base letters only, e.g. "role" = "Rôle" = "rôle".
+    Primary,
+    /// Accents too, e.g. "role" < "Rôle" = "rôle".
+    Secondary,
+    /// Case and variants of characters too, e.g. "role" < "rôle" < "Rôle".
+    Tertiary,
+    /// Also the whitespace, punctuation and symbols when they are shifted, e.g. "a-b" < "ab".
+    /// Same as tertiary with `VariableWeighting::NonIgnorable`.
+    Quaternary,
+    /// Any strings that are not canonically equivalent differ.
+    Identical,
+}
+
+/// Handling of the variable characters, whitespace, punctuation and most symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VariableWeighting {
+    /// Variable characters are ordered like the letters, e.g. "a b" < "a-c" < "ab" < "ac".
+    NonIgnorable,
+    /// Variable characters only make a difference at the quaternary level, e.g. "a b" < "ab" <
+    /// "a-c" < "ac".
+    Shifted,
+}
+
+/// Order of the lowercase and uppercase letters at the tertiary level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CaseFirst {
+    /// Lowercase letters go first, e.g. "a" < "A", as in the DUCET.
+    Lower,
+    /// Uppercase letters go first, e.g. "A" < "a".
+    Upper,
+}
+
+/// Collation by the Unicode Collation Algorithm with the DUCET.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UcaCollation {
+    pub strength: Strength,
+    pub variable_weighting: VariableWeighting,
+    pub case_first: CaseFirst,
+    /// Whether sequences of decimal digits are ordered by their numeric value, e.g. "a2" < "a10".
+    pub numeric: bool,
+}
+
+impl Default for UcaCollation {
+    fn default() -> Self {
+        UcaCollation {
+            strength: Strength::Tertiary,
+            variable_weighting: VariableWeighting::NonIgnorable,
+            case_first: CaseFirst::Lower,
+            numeric: false,
+        }
+    }
+}
+
+impl UcaCollation {
+    /// Returns the collation elements of the decomposed string, grouped by the characters or
+    /// contractions they come from.
+    fn collation_elements(&self, chars: &[char]) -> Vec<Vec<Element>> {
+        let table = Table::ducet();
+        let mut chars = chars.to_vec();
+        let mut units = Vec::with_capacity(chars.len());
+        let mut i = 0;
+        while i < chars.len() {
+            let longest = table.longest_contraction(chars[i]);
+            let length = (2..longest.min(chars.len() - i) + 1).rev()
+                .find(|&n| table.get(&chars[i..i + n]).is_some())
+                .unwrap_or(1);
+            let mut matched = chars[i..i + length].to_vec();
+            if longest > 1 {
+                // discontiguous contractions skip the non-starters that do not contract, unless
+                // one with the same combining class was skipped, which blocks them
+                let mut j = i + length;
+                let mut skipped_class = 0;
+                while j < chars.len() {
+                    let class = combining_class(chars[j]);
+                    if class == 0 {
+                        break;
+                    }
+                    matched.push(chars[j]);
+                    if class != skipped_class && table.get(&matched).is_some() {
+                        chars.remove(j);
+                    } else {
+                        matched.pop();
+                        skipped_class = class;
+                        j += 1;
+                    }
+                }
+            }
+            units.push(match table.get(&matched) {
+                Some(elements) => elements.to_vec(),
+                None => table.implicit(chars[i]).to_vec(),
+            });
+            i += length;
+        }
+        units
+    }
+
+    /// Maps the tertiary weight according to the case ordering.
+    fn tertiary(&self, weight: u16) -> u16 {
+        match (self.case_first, weight) {
+            // the DUCET lowercase (and uncased) and uppercase variants swap places
+            (CaseFirst::Upper, 0x02..=0x06) => weight + 6,
+            (CaseFirst::Upper, 0x08..=0x0C) => weight - 6,
+            (CaseFirst::Upper, 0x1C) => 0x1D,
+            (CaseFirst::Upper, 0x1D) => 0x1C,
+            _ => weight,
+        }
+    }
+}
+
+/// Replaces the elements of each sequence of decimal digits with elements ordering it by the
+/// numeric value: the number of significant digits followed by the digits themselves.
+fn numeric(units: Vec<Vec<Element>>) -> Vec<Vec<Element>> {
+    let zero = Table::ducet().get(&['0']).expect("missing digit zero")[0];
+    let digit = |unit: &[Element]| match unit {
+        [element] if zero.primary <= element.primary && element.primary <= zero.primary + 9 => {
+            Some(element.primary - zero.primary)
+        }
+        _ => None,
+    };
+    let mut result = Vec::with_capacity(units.len());
+    let mut i = 0;
+    while i < units.len() {
+        if digit(&units[i]).is_none() {
+            result.push(units[i].clone());
+            i += 1;
+            continue;
+        }
+        let end = i + units[i..].iter().take_while(|unit| digit(unit).is_some()).count();
+        // leading zeros do not change the value, but zero itself still has one digit
+        let start = i + units[i..end - 1].iter().take_while(|unit| digit(unit) == Some(0)).count();
+        // the length is written with digits one to eight, each nine adding eight more
+        let length_digit = |n: usize| vec![Element { primary: zero.primary + n as u16, ..zero }];
+        let mut length = end - start;
+        while length > 8 {
+            result.push(length_digit(9));
+            length -= 8;
+        }
+        result.push(length_digit(length));
+        result.extend_from_slice(&units[start..end]);
+        i = end;
+    }
+    result
+}
+
+impl Collation for UcaCollation {
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        self.sort_key(a).cmp(&self.sort_key(b))
+    }
+
+    fn sort_key(&self, s: &str) -> Vec<u8> {
+        let chars = normalize::nfd(s);
+        let mut units = self.collation_elements(&chars);
+        if self.numeric {
+            units = numeric(units);
+        }
+        let shifted = self.variable_weighting == VariableWeighting::Shifted;
+        let mut levels: [Vec<u16>; 4] = Default::default();
+        let mut after_variable = false;
+        for element in units.iter().flatten().filter(|element| !element.is_ignorable()) {
+            let weights = if shifted && element.variable {
+                after_variable = true;
+                [0, 0, 0, element.primary]
+            } else if shifted && element.primary == 0 && after_variable {
+                [0, 0, 0, 0]
+            } else {
+                after_variable = false;
+                [element.primary, element.secondary, self.tertiary(element.tertiary), 0xFFFF]
+            };
+            for (level, &weight) in levels.iter_mut().zip(&weights) {
+                if weight != 0 {
+                    level.push(weight);
+                }
+            }
+        }
+        let count = match self.strength {
+            Strength::Primary => 1,
+            Strength::Secondary => 2,
+            Strength::Tertiary => 3,
+            Strength::Quaternary | Strength::Identical if shifted => 4,
+            Strength::Quaternary | Strength::Identical => 3,
+        };
+        let mut key = Vec::new();
+        for (i, level) in levels[..count].iter().enumerate() {
+            if i > 0 {
+                key.extend_from_slice(&[0, 0]);
+            }
+            for weight in level {
+                key.extend_from_slice(&weight.to_be_bytes());
+            }
+        }
+        if self.strength == Strength::Identical {
+            // UTF-8 is ordered by code points
+            key.extend_from_slice(&[0, 0]);
+            key.extend(chars.into_iter().collect::<String>().bytes());
+        }
+        key
+    }
+}
+
+/// Factory of locales with the collation by the Unicode Collation Algorithm.
+///
+/// It only provides the `Collator` facet, so combine it with other factories with
+/// `CompositeLocaleFactory`.
+#[derive(Debug, Clone, Default)]
+pub struct UcaLocaleFactory {
+    /// The collation the collators use.
+    pub collation: UcaCollation,
+}
+
+impl UcaLocaleFactory {
+    /// Constructs the factory with the default collation options.
+    ///
+    /// The DUCET is not tailored to particular languages, so the locale does not make a
+    /// difference; the parameter is there so the signature matches the other locale factories.
+    #[allow(unused_variables)]
+    pub fn new(locale: &str) -> Result<Self> {
+        Ok(UcaLocaleFactory::default())
+    }
+}
+
+impl LocaleFactory for UcaLocaleFactory {
+    fn get_collator(&mut self) -> Option<Box<Collator>> {
+        Some(Box::new(Collator::new(Box::new(self.collation))))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ::std::fs;
+    use ::std::path::Path;
+
+    /// Checks the collation orders the strings in the file, in the format of the UCA conformance
+    /// tests, `CollationTest_*_SHORT.txt`: each line must not sort before the previous one at the
+    /// quaternary strength, and equal ones are in order of code points.
+    fn check_conformance(file: &str, variable_weighting: VariableWeighting) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/uca").join(file);
+        let text = fs::read_to_string(path).unwrap();
+        let collation = UcaCollation {
+            strength: Strength::Quaternary,
+            variable_weighting,
+            ..UcaCollation::default()
+        };
+        let mut previous: Option<(String, Vec<u8>)> = None;
+        let mut failures = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() || line.starts_with('@') {
+                continue;
+            }
+            let string: Option<String> = line.split(' ')
+                .map(|hex| u32::from_str_radix(hex, 16).ok().and_then(::std::char::from_u32))
+                .collect();
+            // strings with unpaired surrogates can't be represented
+            let string = match string {
+                Some(string) => string,
+                None => continue,
+            };
+            let key = collation.sort_key(&string);
+            if let Some((previous_string, previous_key)) = previous {
+                // equal strings are ordered by code points
+                let order = previous_key.cmp(&key).then(previous_string.cmp(&string));
+                if order == Ordering::Greater && failures.len() < 10 {
+                    failures.push((number + 1, line));
+                }
+            }
+            previous = Some((string, key));
+        }
+        assert_eq!(Vec::<(usize, &str)>::new(), failures);
+    }
+
+    #[test]
+    fn conformance_non_ignorable() {
+        check_conformance("non_ignorable.txt", VariableWeighting::NonIgnorable);
+    }
+
+    #[test]
+    fn conformance_shifted() {
+        check_conformance("shifted.txt", VariableWeighting::Shifted);
+    }
+
+    // the official files are in https://www.unicode.org/Public/UCA/13.0.0/CollationTest.zip
+    #[test]
+    #[ignore = "needs CollationTest_NON_IGNORABLE_SHORT.txt of UCA 13.0.0 in tests/uca"]
+    fn conformance_official_non_ignorable() {
+        check_conformance("CollationTest_NON_IGNORABLE_SHORT.txt", VariableWeighting::NonIgnorable);
+    }
+
+    #[test]
+    #[ignore = "needs CollationTest_SHIFTED_SHORT.txt of UCA 13.0.0 in tests/uca"]
+    fn conformance_official_shifted() {
+        check_conformance("CollationTest_SHIFTED_SHORT.txt", VariableWeighting::Shifted);
+    }
+
+    fn sorted(collation: UcaCollation, strings: &[&str]) -> Vec<String> {
+        let mut strings: Vec<String> = strings.iter().map(|s| s.to_string()).collect();
+        Collator::new(Box::new(collation)).sort(&mut strings);
+        strings
+    }
+
+    #[test]
+    fn strength() {
+        let mut collation = UcaCollation::default();
+        let order = |collation: &UcaCollation| {
+            (collation.compare("role", "rôle"), collation.compare("rôle", "Rôle"))
+        };
+        collation.strength = Strength::Primary;
+        assert_eq!((Ordering::Equal, Ordering::Equal), order(&collation));
+        collation.strength = Strength::Secondary;
+        assert_eq!((Ordering::Less, Ordering::Equal), order(&collation));
+        collation.strength = Strength::Tertiary;
+        assert_eq!((Ordering::Less, Ordering::Less), order(&collation));
+        assert_eq!(Ordering::Equal, collation.compare("\u{c5}", "A\u{30a}"));
+        collation.strength = Strength::Identical;
+        assert_eq!(Ordering::Equal, collation.compare("\u{c5}", "A\u{30a}"));
+        assert_eq!(Ordering::Less, collation.compare("\u{0}b", "b"));
+    }
+
+    #[test]
+    fn variable_weighting() {
+        let strings = ["ac", "a-c", "ab", "a b"];
+        let mut collation = UcaCollation::default();
+        assert_eq!(vec!["a b", "a-c", "ab", "ac"], sorted(collation, &strings));
+        collation.variable_weighting = VariableWeighting::Shifted;
+        assert_eq!(Ordering::Equal, collation.compare("ab", "a-b"));
+        collation.strength = Strength::Quaternary;
+        assert_eq!(vec!["a b", "ab", "a-c", "ac"], sorted(collation, &strings));
+    }
+
+    #[test]
+    fn case_first() {
+        let strings = ["b", "A", "a", "B", "\u{e1}", "\u{c1}"];
+        let mut collation = UcaCollation::default();
+        assert_eq!(vec!["a", "A", "\u{e1}", "\u{c1}", "b", "B"], sorted(collation, &strings));
+        collation.case_first = CaseFirst::Upper;
+        assert_eq!(vec!["A", "a", "\u{c1}", "\u{e1}", "B", "b"], sorted(collation, &strings));
+        // the case only matters at the tertiary level
+        assert_eq!(Ordering::Less, collation.compare("Ab", "aC"));
+    }
+
+    #[test]
+    fn numeric() {
+        let strings = ["a10", "a9", "a1000000000", "a99999999", "a0", "a00", "b1", "a"];
+        let mut collation = UcaCollation::default();
+        assert_eq!(vec!["a", "a0", "a00", "a10", "a1000000000", "a9", "a99999999", "b1"],
+                   sorted(collation, &strings));
+        collation.numeric = true;
+        assert_eq!(vec!["a", "a0", "a00", "a9", "a10", "a99999999", "a1000000000", "b1"],
+                   sorted(collation, &strings));
+        // other scripts' digits have the same values
+        assert_eq!(Ordering::Less, collation.compare("\u{664}", "12"));
+        collation.strength = Strength::Primary;
+        assert_eq!(Ordering::Equal, collation.compare("a010", "a10"));
+        assert_eq!(Ordering::Equal, collation.compare("\u{661}\u{662}", "12"));
+    }
+
+    #[test]
+    fn factory() {
+        let mut factory = UcaLocaleFactory::new("cs_CZ").unwrap();
+        factory.collation.strength = Strength::Primary;
+        let collator = factory.get_collator().unwrap();
+        assert_eq!(Ordering::Equal, collator.compare("\u{10d}", "C"));
+        assert_eq!(Ordering::Less, collator.compare("ch", "h"));
+    }
+}
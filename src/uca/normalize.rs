@@ -0,0 +1,109 @@
+//! Canonical decomposition (NFD) of strings, which the collation algorithm works on.
+
+use ::std::collections::HashMap;
+use ::std::sync::OnceLock;
+
+const HANGUL_S_BASE: u32 = 0xAC00;
+const HANGUL_L_BASE: u32 = 0x1100;
+const HANGUL_V_BASE: u32 = 0x1161;
+const HANGUL_T_BASE: u32 = 0x11A7;
+const HANGUL_V_COUNT: u32 = 21;
+const HANGUL_T_COUNT: u32 = 28;
+const HANGUL_S_COUNT: u32 = 19 * HANGUL_V_COUNT * HANGUL_T_COUNT;
+
+struct Data {
+    classes: HashMap<char, u8>,
+    decompositions: HashMap<char, Vec<char>>,
+}
+
+fn data() -> &'static Data {
+    static DATA: OnceLock<Data> = OnceLock::new();
+    DATA.get_or_init(|| {
+        let mut data = Data { classes: HashMap::new(), decompositions: HashMap::new() };
+        for line in include_str!("normalization.txt").lines().filter(|l| !l.starts_with('#')) {
+            let fields: Vec<&str> = line.split(';').collect();
+            let c = parse_char(fields[0]);
+            let class = fields[1].parse().expect("invalid combining class");
+            if class != 0 {
+                data.classes.insert(c, class);
+            }
+            if !fields[2].is_empty() {
+                data.decompositions.insert(c, fields[2].split(' ').map(parse_char).collect());
+            }
+        }
+        data
+    })
+}
+
+/// Parses character from its code point in hexadecimal, as in the Unicode data files.
+pub fn parse_char(hex: &str) -> char {
+    u32::from_str_radix(hex, 16).ok().and_then(::std::char::from_u32).expect("invalid code point")
+}
+
+/// Returns the canonical combining class of the character, which is 0 for starters.
+pub fn combining_class(c: char) -> u8 {
+    data().classes.get(&c).cloned().unwrap_or(0)
+}
+
+/// Returns the characters of the string in Normalization Form D.
+pub fn nfd(s: &str) -> Vec<char> {
+    let mut chars = Vec::with_capacity(s.len());
+    for c in s.chars() {
+        decompose(c, &mut chars);
+    }
+    // canonical ordering sorts each sequence of non-starters by the combining class
+    let mut start = 0;
+    while start < chars.len() {
+        let end = start + chars[start..].iter().take_while(|&&c| combining_class(c) != 0).count();
+        chars[start..end].sort_by_key(|&c| combining_class(c));
+        start = end + 1;
+    }
+    chars
+}
+
+fn decompose(c: char, chars: &mut Vec<char>) {
+    let code = c as u32;
+    if (HANGUL_S_BASE..HANGUL_S_BASE + HANGUL_S_COUNT).contains(&code) {
+        let index = code - HANGUL_S_BASE;
+        let (lv, t) = (index / HANGUL_T_COUNT, index % HANGUL_T_COUNT);
+        let jamo = |code| ::std::char::from_u32(code).unwrap();
+        chars.push(jamo(HANGUL_L_BASE + lv / HANGUL_V_COUNT));
+        chars.push(jamo(HANGUL_V_BASE + lv % HANGUL_V_COUNT));
+        if t != 0 {
+            chars.push(jamo(HANGUL_T_BASE + t));
+        }
+    } else if let Some(decomposition) = data().decompositions.get(&c) {
+        chars.extend_from_slice(decomposition);
+    } else {
+        chars.push(c);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn nfd_string(s: &str) -> String {
+        nfd(s).into_iter().collect()
+    }
+
+    #[test]
+    fn decomposition() {
+        assert_eq!("A\u{30a}", nfd_string("\u{c5}"));
+        assert_eq!("A\u{30a}", nfd_string("\u{212b}"));
+        assert_eq!("C\u{327}\u{301}", nfd_string("\u{1e08}"));
+        assert_eq!("\u{1100}\u{1161}", nfd_string("\u{ac00}"));
+        assert_eq!("\u{1112}\u{1175}\u{11c2}", nfd_string("\u{d7a3}"));
+        assert_eq!("plain", nfd_string("plain"));
+    }
+
+    #[test]
+    fn canonical_ordering() {
+        assert_eq!("a\u{316}\u{301}", nfd_string("a\u{301}\u{316}"));
+        assert_eq!("a\u{316}\u{301}", nfd_string("\u{e1}\u{316}"));
+        // marks of the same class keep their order
+        assert_eq!("a\u{301}\u{300}b\u{334}", nfd_string("a\u{301}\u{300}b\u{334}"));
+        assert_eq!(230, combining_class('\u{301}'));
+        assert_eq!(0, combining_class('a'));
+    }
+}
@@ -0,0 +1,187 @@
+//! The Default Unicode Collation Element Table and the implicit weights of characters missing in
+//! it.
+
+use ::std::collections::HashMap;
+use ::std::sync::OnceLock;
+
+use super::normalize::parse_char;
+
+/// Unified ideographs of the CJK Unified Ideographs and CJK Compatibility Ideographs blocks, as of
+/// Unicode 13.0.0.
+const CORE_HAN: &[(u32, u32)] = &[
+    (0x4E00, 0x9FFC), (0xFA0E, 0xFA0F), (0xFA11, 0xFA11), (0xFA13, 0xFA14), (0xFA1F, 0xFA1F),
+    (0xFA21, 0xFA21), (0xFA23, 0xFA24), (0xFA27, 0xFA29),
+];
+
+/// Unified ideographs of the extension blocks, as of Unicode 13.0.0.
+const OTHER_HAN: &[(u32, u32)] = &[
+    (0x3400, 0x4DBF), (0x20000, 0x2A6DD), (0x2A700, 0x2B734), (0x2B740, 0x2B81D),
+    (0x2B820, 0x2CEA1), (0x2CEB0, 0x2EBE0), (0x30000, 0x3134A),
+];
+
+/// Assigned characters of the scripts the table gives implicit weights for, as of Unicode 13.0.0.
+/// The table lists whole blocks, but the unassigned code points in them get the usual weights.
+const ASSIGNED_SIEVED: &[(u32, u32)] = &[
+    (0x17000, 0x187F7), (0x18800, 0x18CD5), (0x18D00, 0x18D08), (0x1B170, 0x1B2FB),
+];
+
+/// Collation element, the weights of a character, or its part, at the first three levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Element {
+    pub primary: u16,
+    pub secondary: u16,
+    pub tertiary: u16,
+    /// Whether the element is of whitespace, punctuation or symbol, which can be ignored.
+    pub variable: bool,
+}
+
+impl Element {
+    pub fn is_ignorable(&self) -> bool {
+        self.primary == 0 && self.secondary == 0 && self.tertiary == 0
+    }
+}
+
+#[derive(Debug)]
+pub struct Table {
+    singles: HashMap<char, Vec<Element>>,
+    contractions: HashMap<Vec<char>, Vec<Element>>,
+    /// Length of the longest contraction starting with the character.
+    longest: HashMap<char, usize>,
+    /// Ranges with implicit weights given by the table, with their lead weight and the first
+    /// character of the ranges with the same lead weight.
+    implicit: Vec<(u32, u32, u16, u32)>,
+}
+
+impl Table {
+    /// Returns the bundled table.
+    pub fn ducet() -> &'static Table {
+        static DUCET: OnceLock<Table> = OnceLock::new();
+        DUCET.get_or_init(|| Table::parse(include_str!("allkeys.txt")))
+    }
+
+    /// Parses the table in the format of `allkeys.txt`.
+    fn parse(text: &str) -> Table {
+        let mut table = Table {
+            singles: HashMap::new(),
+            contractions: HashMap::new(),
+            longest: HashMap::new(),
+            implicit: Vec::new(),
+        };
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if let Some(implicit) = line.strip_prefix("@implicitweights") {
+                let (range, weight) = implicit.split_once(';').expect("invalid implicit weights");
+                let (first, last) = range.trim().split_once("..").expect("invalid range");
+                let range = (parse_char(first) as u32, parse_char(last) as u32);
+                let weight = u16::from_str_radix(weight.trim(), 16).expect("invalid weight");
+                table.implicit.push((range.0, range.1, weight, range.0));
+                continue;
+            }
+            if line.is_empty() || line.starts_with('@') {
+                continue;
+            }
+            let (chars, elements) = line.split_once(';').expect("invalid entry");
+            let chars: Vec<char> = chars.split_whitespace().map(parse_char).collect();
+            let elements = elements.trim().split('[').skip(1).map(|element| {
+                let weights: Vec<u16> = element[1..].trim_end_matches(']').split('.')
+                    .map(|weight| u16::from_str_radix(weight, 16).expect("invalid weight"))
+                    .collect();
+                Element {
+                    primary: weights[0],
+                    secondary: weights[1],
+                    tertiary: weights[2],
+                    variable: element.starts_with('*'),
+                }
+            }).collect();
+            if chars.len() == 1 {
+                table.singles.insert(chars[0], elements);
+            } else {
+                let longest = table.longest.entry(chars[0]).or_insert(1);
+                *longest = chars.len().max(*longest);
+                table.contractions.insert(chars, elements);
+            }
+        }
+        // the offsets in ranges sharing the lead weight are from the first of them
+        let implicit = table.implicit.clone();
+        for range in &mut table.implicit {
+            range.3 = implicit.iter().filter(|other| other.2 == range.2).map(|other| other.0).min()
+                .unwrap_or(range.0);
+        }
+        table
+    }
+
+    /// Returns the elements of a character or a contraction, if the table has them.
+    pub fn get(&self, chars: &[char]) -> Option<&[Element]> {
+        match chars.len() {
+            1 => self.singles.get(&chars[0]),
+            _ => self.contractions.get(chars),
+        }.map(|elements| &elements[..])
+    }
+
+    /// Returns the length of the longest contraction starting with the character, 1 if there is
+    /// none.
+    pub fn longest_contraction(&self, c: char) -> usize {
+        self.longest.get(&c).cloned().unwrap_or(1)
+    }
+
+    /// Returns the elements of a character not in the table, which sort in order of code points
+    /// after all the others, but ideographs first.
+    pub fn implicit(&self, c: char) -> [Element; 2] {
+        let code = c as u32;
+        let in_ranges = |ranges: &[(u32, u32)]| {
+            ranges.iter().any(|&(first, last)| first <= code && code <= last)
+        };
+        let sieved = self.implicit.iter()
+            .find(|&&(first, last, _, _)| first <= code && code <= last)
+            .filter(|_| in_ranges(ASSIGNED_SIEVED));
+        let (lead, offset) = match sieved {
+            Some(&(_, _, lead, start)) => (lead, code - start),
+            None if in_ranges(CORE_HAN) => (0xFB40 + (code >> 15) as u16, code & 0x7FFF),
+            None if in_ranges(OTHER_HAN) => (0xFB80 + (code >> 15) as u16, code & 0x7FFF),
+            None => (0xFBC0 + (code >> 15) as u16, code & 0x7FFF),
+        };
+        [
+            Element { primary: lead, secondary: 0x20, tertiary: 0x02, variable: false },
+            Element { primary: offset as u16 | 0x8000, secondary: 0, tertiary: 0, variable: false },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn entries() {
+        let table = Table::ducet();
+        let a = Element { primary: 0x1FA2, secondary: 0x20, tertiary: 0x02, variable: false };
+        assert_eq!(Some(&[a][..]), table.get(&['a']));
+        assert!(table.get(&[' ']).unwrap()[0].variable);
+        assert!(table.get(&['\0']).unwrap()[0].is_ignorable());
+        assert_eq!(3, table.get(&['\u{1e9e}']).unwrap().len());
+        assert_eq!(2, table.get(&['L', '\u{b7}']).unwrap().len());
+        assert_eq!(None, table.get(&['a', 'b']));
+        assert_eq!(2, table.longest_contraction('L'));
+        assert_eq!(1, table.longest_contraction('a'));
+    }
+
+    #[test]
+    fn implicit_weights() {
+        let table = Table::ducet();
+        let primaries = |c: char| {
+            let elements = table.implicit(c);
+            (elements[0].primary, elements[1].primary)
+        };
+        assert_eq!((0xFB40, 0xCE00), primaries('\u{4e00}'));
+        assert_eq!((0xFB41, 0xFA0E), primaries('\u{fa0e}'));
+        assert_eq!((0xFB80, 0xB400), primaries('\u{3400}'));
+        assert_eq!((0xFB84, 0x8000), primaries('\u{20000}'));
+        assert_eq!((0xFBC1, 0x9FFD), primaries('\u{9ffd}'));
+        assert_eq!((0xFBE1, 0xFFFF), primaries('\u{10ffff}'));
+        assert_eq!((0xFB00, 0x8000), primaries('\u{17000}'));
+        assert_eq!((0xFB00, 0x9D00), primaries('\u{18d00}'));
+        assert_eq!((0xFB01, 0x8001), primaries('\u{1b171}'));
+        assert_eq!((0xFB02, 0x8000), primaries('\u{18b00}'));
+        assert_eq!((0xFBC3, 0x8D09), primaries('\u{18d09}'));
+    }
+}
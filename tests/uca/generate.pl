@@ -0,0 +1,107 @@
+#!/usr/bin/perl
+# Generates small supplementary collation tests in the format of CollationTest_*_SHORT.txt of
+# the UCA conformance test suite, with Unicode::Collate of UCA 13.0.0 as the reference
+# implementation and the DUCET bundled in src/uca:
+#
+#     perl generate.pl non-ignorable > non_ignorable.txt
+#     perl generate.pl shifted > shifted.txt
+#
+# Each line is a string written as hexadecimal code points and the strings are sorted by their
+# sort keys up to the quaternary level, ties broken by code point order, like in the official
+# files. The strings focus on what the official files test less: characters with implicit
+# weights, Hangul and combining characters exercising normalization and discontiguous
+# contractions, with a sample of the DUCET entries.
+
+use strict;
+use warnings;
+no warnings 'utf8';
+use FindBin;
+use Unicode::Collate;
+
+my $variable = shift or die "usage: $0 non-ignorable|shifted\n";
+die "unexpected UCA version\n" unless Unicode::Collate::UCA_Version() eq '43';
+
+my $allkeys = "$FindBin::Bin/../../src/uca/allkeys.txt";
+my ($table, @entries, @contractions);
+open my $file, '<', $allkeys or die "$allkeys: $!\n";
+while (<$file>) {
+    next unless /^([0-9A-F ]+?)\s*;/;
+    $table .= $_;
+    my @code_points = map hex, split ' ', $1;
+    push @entries, \@code_points;
+    push @contractions, \@code_points if @code_points > 1;
+}
+my $collator = Unicode::Collate->new(
+    table => undef,
+    entry => $table,
+    variable => $variable,
+    level => $variable eq 'shifted' ? 4 : 3,
+);
+
+# every 64th entry followed by `!` and `a`, as the official files have all of them
+my @strings;
+for (my $index = 0; $index < @entries; $index += 64) {
+    push @strings, [@{$entries[$index]}, 0x21], [@{$entries[$index]}, 0x61];
+}
+
+# implicit weights: the ideograph and Tangut, Nushu and Khitan ranges and unassigned code points
+my @implicit = (
+    0x4E00, 0x4E01, 0x9FFC, 0x9FFD, 0xFA0E, 0xFA0F, 0xFA10, 0xFA11, 0xFA29, 0xFA2A,
+    0x3400, 0x4DBF, 0x4DC0, 0x20000, 0x2A6DD, 0x2A6DE, 0x2A700, 0x2B734, 0x2B740, 0x2B81D,
+    0x2B820, 0x2CEA1, 0x2CEB0, 0x2EBE0, 0x30000, 0x3134A, 0x3134B,
+    0x17000, 0x187F7, 0x18AFF, 0x18D00, 0x18D08, 0x18D8F, 0x1B170, 0x1B2FB, 0x1B2FF,
+    0x18B00, 0x18CD5, 0x18CFF,
+    0x0378, 0x0870, 0x2FFFF, 0xE0000, 0xE0080, 0xFFFE, 0xFFFF, 0xFDD0, 0x10FFFF,
+);
+for my $code_point (@implicit) {
+    push @strings, [$code_point], [$code_point, 0x21], [$code_point, 0x61],
+        [$code_point, 0x0301], [0x61, $code_point];
+}
+
+# Hangul syllables, which are decomposed to jamo, and the jamo sequences
+for (my $syllable = 0xAC00; $syllable <= 0xD7A3; $syllable += 97) {
+    push @strings, [$syllable], [$syllable, 0x61];
+}
+push @strings, [0xD7A3], [0x1100, 0x1161], [0x1100, 0x1161, 0x11A8], [0x1100, 0x1161, 0x0021];
+
+# non-starters with various combining classes inserted into and after the contractions, one of
+# each block and all the longer ones
+my @marks = (0x0334, 0x0316, 0x0300, 0x0301, 0x0345);
+my %blocks;
+for my $contraction (grep { @$_ > 2 || !$blocks{$_->[0] >> 7}++ } @contractions) {
+    my ($first, @rest) = @$contraction;
+    for my $mark (@marks) {
+        push @strings, [$first, $mark, @rest], [@$contraction, $mark],
+            [$first, $mark, @rest, 0x61];
+        push @strings, [$first, $rest[0], $mark, @rest[1 .. $#rest]] if @rest > 1;
+    }
+}
+
+# canonically equivalent strings and ignorables next to variable characters
+push @strings,
+    [0x00C5], [0x0041, 0x030A], [0x212B], [0x00E1, 0x0316], [0x0061, 0x0316, 0x0301],
+    [0x0061, 0x0301, 0x0316], [0x1E09], [0x0063, 0x0301, 0x0327], [0x0063, 0x0327, 0x0301],
+    [0x0021, 0x0301, 0x0061], [0x0061, 0x0021, 0x0301], [0x0061, 0x0000, 0x0062],
+    [0x0020, 0x0334, 0x0061], [0x0061, 0x0020, 0x0062], [0x0061, 0x0062], [0x0061, 0x002D, 0x0062],
+    [0x0301, 0x0061], [0x0061, 0x00AD, 0x0062], [0x0021], [0x0061];
+
+my %seen;
+my @lines = grep { !$seen{$_}++ } map { join ' ', map { sprintf '%04X', $_ } @$_ } @strings;
+my %keys = map {
+    my $line = $_;
+    $line => $collator->getSortKey(join '', map { chr hex } split ' ', $line)
+} @lines;
+print "# Collation test for variable weighting $variable, generated by generate.pl.\n";
+for my $line (sort { $keys{$a} cmp $keys{$b} || compare_code_points($a, $b) } @lines) {
+    print "$line\n";
+}
+
+sub compare_code_points {
+    my @a = map hex, split ' ', shift;
+    my @b = map hex, split ' ', shift;
+    while (@a && @b) {
+        my $order = shift(@a) <=> shift(@b);
+        return $order if $order;
+    }
+    return @a <=> @b;
+}